@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use crate::import::{fields_from_header, SourceRows};
+use crate::model::{ColumnField, ColumnMapping, Flashcard};
+use crate::sqlite::{Database, Row};
+
+/// Review interval, in days, from which Anki considers a card mature.
+pub const MATURE_INTERVAL_DAYS: i64 = 21;
+const REVIEW_CARD: i64 = 2;
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+// Column positions in the legacy collection schema.
+const NOTE_MODEL: usize = 2;
+const NOTE_TAGS: usize = 5;
+const NOTE_FIELDS: usize = 6;
+const CARD_NOTE: usize = 1;
+const CARD_DECK: usize = 2;
+const CARD_TYPE: usize = 6;
+const CARD_INTERVAL: usize = 9;
+const COL_MODELS: usize = 9;
+const COL_DECKS: usize = 10;
+
+/// Notes of an Anki deck as import rows: a header with the note fields followed by
+/// `Tags` and `Known`, then one row per note.
+pub struct AnkiDeck {
+    pub name: String,
+    pub source: SourceRows,
+    pub mapping: ColumnMapping,
+}
+
+/// Turns field HTML into plain text: tags and sound references are dropped, line
+/// breaks become spaces and the common entities are decoded.
+pub fn field_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(['<', '[']) {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = if rest.starts_with('<') {
+            rest.find('>')
+        } else if rest.starts_with("[sound:") {
+            rest.find(']')
+        } else {
+            None
+        };
+        match end {
+            Some(end) => {
+                text.push(' ');
+                rest = &rest[end + 1..];
+            }
+            None => {
+                text.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Option<Vec<u8>> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+fn collection_bytes(apkg: &[u8]) -> Result<Vec<u8>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(apkg))
+        .map_err(|_| "This file is not an Anki deck package (.apkg).".to_string())?;
+    if let Some(bytes) = read_entry(&mut archive, "collection.anki21") {
+        return Ok(bytes);
+    }
+    if archive.by_name("collection.anki21b").is_ok() {
+        return Err(
+            "This deck was exported in the compressed format of Anki 2.1.50 or newer. \
+            Export it again with \"Support older Anki versions\" checked."
+                .to_string(),
+        );
+    }
+    read_entry(&mut archive, "collection.anki2")
+        .ok_or_else(|| "The deck package does not contain an Anki collection.".to_string())
+}
+
+/// Field names of each note type, by note type id.
+fn note_type_fields(database: &Database, col: Option<&Row>) -> HashMap<i64, Vec<String>> {
+    let mut fields: HashMap<i64, Vec<String>> = HashMap::new();
+    let models = col
+        .map(|col| col.get(COL_MODELS).as_text())
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+    if let Some(models) = models.as_ref().and_then(|models| models.as_object()) {
+        for (id, model) in models {
+            let Ok(id) = id.parse() else {
+                continue;
+            };
+            let names = model["flds"]
+                .as_array()
+                .map(|flds| {
+                    flds.iter()
+                        .map(|field| field["name"].as_str().unwrap_or_default().to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            fields.insert(id, names);
+        }
+    }
+
+    // Newer collections keep note types in their own tables.
+    if fields.is_empty() {
+        if let Ok(rows) = database.table("fields") {
+            let mut sorted: Vec<_> = rows
+                .iter()
+                .map(|row| {
+                    (
+                        row.get(0).as_integer(),
+                        row.get(1).as_integer(),
+                        row.get(2).as_text(),
+                    )
+                })
+                .collect();
+            sorted.sort();
+            for (note_type, _, name) in sorted {
+                fields.entry(note_type).or_default().push(name);
+            }
+        }
+    }
+    fields
+}
+
+fn deck_names(database: &Database, col: Option<&Row>) -> HashMap<i64, String> {
+    let mut names = HashMap::new();
+    let decks = col
+        .map(|col| col.get(COL_DECKS).as_text())
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+    if let Some(decks) = decks.as_ref().and_then(|decks| decks.as_object()) {
+        for (id, deck) in decks {
+            if let (Ok(id), Some(name)) = (id.parse(), deck["name"].as_str()) {
+                names.insert(id, name.to_string());
+            }
+        }
+    }
+    if names.is_empty() {
+        if let Ok(rows) = database.table("decks") {
+            for row in rows {
+                names.insert(row.get(0).as_integer(), row.get(1).as_text());
+            }
+        }
+    }
+    names
+        .into_iter()
+        .map(|(id, name)| (id, name.replace(FIELD_SEPARATOR, "::").replace("::", " / ")))
+        .collect()
+}
+
+/// Reads the notes of an `.apkg` deck package. A note counts as known when all of
+/// its cards are in review with an interval of at least [`MATURE_INTERVAL_DAYS`].
+pub fn read_apkg(apkg: &[u8], file_stem: &str) -> Result<AnkiDeck, String> {
+    let collection = collection_bytes(apkg)?;
+    let database = Database::open(&collection)
+        .map_err(|error| format!("Unreadable Anki collection: {error}"))?;
+    let read = |table: &str| {
+        database
+            .table(table)
+            .map_err(|error| format!("Unreadable Anki collection: {error}"))
+    };
+    let notes = read("notes")?;
+    let cards = read("cards")?;
+    let col = database.table("col").ok().and_then(|mut rows| rows.pop());
+
+    let mut mature: HashMap<i64, bool> = HashMap::new();
+    let mut deck_counts: HashMap<i64, usize> = HashMap::new();
+    for card in &cards {
+        let is_mature = card.get(CARD_TYPE).as_integer() == REVIEW_CARD
+            && card.get(CARD_INTERVAL).as_integer() >= MATURE_INTERVAL_DAYS;
+        *mature
+            .entry(card.get(CARD_NOTE).as_integer())
+            .or_insert(true) &= is_mature;
+        *deck_counts
+            .entry(card.get(CARD_DECK).as_integer())
+            .or_default() += 1;
+    }
+
+    let name = deck_counts
+        .into_iter()
+        .max_by_key(|(id, count)| (*count, -*id))
+        .and_then(|(id, _)| deck_names(&database, col.as_ref()).remove(&id))
+        .filter(|name| name != "Default")
+        .unwrap_or_else(|| file_stem.to_string());
+
+    let note_types = note_type_fields(&database, col.as_ref());
+    let mut type_counts: HashMap<i64, usize> = HashMap::new();
+    for note in &notes {
+        *type_counts
+            .entry(note.get(NOTE_MODEL).as_integer())
+            .or_default() += 1;
+    }
+    let main_type = type_counts
+        .into_iter()
+        .max_by_key(|(id, count)| (*count, -*id))
+        .map(|(id, _)| id);
+
+    let field_count = notes
+        .iter()
+        .map(|note| {
+            note.get(NOTE_FIELDS)
+                .as_text()
+                .split(FIELD_SEPARATOR)
+                .count()
+        })
+        .max()
+        .unwrap_or(0);
+    let known_names = main_type
+        .and_then(|id| note_types.get(&id))
+        .cloned()
+        .unwrap_or_default();
+    let mut header: Vec<String> = (0..field_count)
+        .map(|index| {
+            known_names
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| format!("Field {}", index + 1))
+        })
+        .collect();
+    header.push("Tags".to_string());
+    header.push("Known".to_string());
+
+    let mut source = SourceRows::default();
+    source.rows.push(header);
+    source.line_numbers.push(1);
+    for note in &notes {
+        let mut row: Vec<String> = note
+            .get(NOTE_FIELDS)
+            .as_text()
+            .split(FIELD_SEPARATOR)
+            .map(field_text)
+            .collect();
+        row.resize(field_count, String::new());
+        row.push(note.get(NOTE_TAGS).as_text().trim().to_string());
+        let known = mature.get(&note.rowid).copied().unwrap_or(false);
+        row.push(if known { "known" } else { "" }.to_string());
+        source.line_numbers.push(source.rows.len() + 1);
+        source.rows.push(row);
+    }
+
+    let mapping = deck_mapping(&source.rows[0], field_count);
+    Ok(AnkiDeck {
+        name,
+        source,
+        mapping,
+    })
+}
+
+/// Maps fields by name, falling back to the first field as the word and the second
+/// as the translation for note types such as Basic whose names are not recognised.
+fn deck_mapping(header: &[String], field_count: usize) -> ColumnMapping {
+    let mut columns = fields_from_header(header, header.len());
+    for (index, field) in [ColumnField::Word, ColumnField::Translation]
+        .into_iter()
+        .enumerate()
+    {
+        if !columns.contains(&field) && index < field_count && columns[index] == ColumnField::Ignore
+        {
+            columns[index] = field;
+        }
+    }
+    ColumnMapping {
+        has_header: true,
+        columns,
+    }
+}
+
+/// Tag given to exported cards that are known here, so they can be found in Anki.
+pub const KNOWN_TAG: &str = "known";
+
+/// Fields of Anki's Basic note type, which every Anki profile has.
+const BASIC_FIELDS: [&str; 2] = ["Front", "Back"];
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Writes cards as a text file for Anki's "Import File". Header lines select tab
+/// separation, the Basic note type and the deck. The word goes to the Front field
+/// and the pinyin above the translation on the Back, since Basic has no field of
+/// its own for it; tags follow in a last column.
+pub fn export_anki_text(deck_name: &str, cards: &[Flashcard]) -> Result<Vec<u8>, csv::Error> {
+    let header = format!(
+        "#separator:tab\n#html:true\n#notetype:Basic\n#deck:{}\n\
+        #columns:{}\tTags\n#tags column:{}\n",
+        deck_name.replace(" / ", "::"),
+        BASIC_FIELDS.join("\t"),
+        BASIC_FIELDS.len() + 1
+    );
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_writer(header.into_bytes());
+
+    for card in cards {
+        let mut tags = card.tags.clone();
+        if card.known && !tags.iter().any(|tag| tag == KNOWN_TAG) {
+            tags.push(KNOWN_TAG.to_string());
+        }
+        let back = match card.pinyin.as_deref().filter(|pinyin| !pinyin.is_empty()) {
+            Some(pinyin) => format!(
+                "{}<br>{}",
+                escape_html(pinyin),
+                escape_html(&card.translation)
+            ),
+            None => escape_html(&card.translation),
+        };
+        writer.write_record([
+            escape_html(&card.word).as_str(),
+            back.as_str(),
+            tags.join(" ").as_str(),
+        ])?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|error| error.into_error().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{export_anki_text, field_text, read_apkg, BASIC_FIELDS};
+    use crate::import::build_report;
+
+    fn apkg(collection_name: &str) -> Vec<u8> {
+        let path = std::env::temp_dir().join(format!(
+            "flashcards-anki-{}-{collection_name}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                r#"
+                CREATE TABLE col (id integer primary key, crt integer, mod integer, scm integer,
+                    ver integer, dty integer, usn integer, ls integer, conf text, models text,
+                    decks text, dconf text, tags text);
+                CREATE TABLE notes (id integer primary key, guid text, mid integer, mod integer,
+                    usn integer, tags text, flds text, sfld integer, csum integer, flags integer,
+                    data text);
+                CREATE TABLE cards (id integer primary key, nid integer, did integer, ord integer,
+                    mod integer, usn integer, type integer, queue integer, due integer,
+                    ivl integer, factor integer, reps integer, lapses integer, left integer,
+                    odue integer, odid integer, flags integer, data text);
+                INSERT INTO col VALUES (1, 0, 0, 0, 11, 0, 0, 0, '{}',
+                    '{"100": {"name": "Chinese", "flds": [{"name": "Hanzi", "ord": 0},
+                        {"name": "Pinyin", "ord": 1}, {"name": "English", "ord": 2}]}}',
+                    '{"1": {"name": "Default"}, "7": {"name": "HSK::HSK 1"}}', '{}', '{}');
+                INSERT INTO notes VALUES
+                    (1, 'a', 100, 0, 0, ' hsk1 food ', '苹果' || char(31) || 'píngguǒ' || char(31) || 'apple<br>[sound:apple.mp3]', 0, 0, 0, ''),
+                    (2, 'b', 100, 0, 0, '', '<b>你好</b>' || char(31) || 'nǐ hǎo' || char(31) || 'hello &amp; hi', 0, 0, 0, '');
+                INSERT INTO cards VALUES
+                    (10, 1, 7, 0, 0, 0, 2, 2, 0, 40, 2500, 5, 0, 0, 0, 0, 0, ''),
+                    (11, 2, 7, 0, 0, 0, 2, 2, 0, 3, 2500, 2, 0, 0, 0, 0, 0, '');
+                "#,
+            )
+            .unwrap();
+        drop(connection);
+        let collection = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        writer.start_file(collection_name, options).unwrap();
+        writer.write_all(&collection).unwrap();
+        writer.start_file("media", options).unwrap();
+        writer.write_all(b"{}").unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn imports_notes_tags_and_mature_cards() {
+        let deck = read_apkg(&apkg("collection.anki2"), "fallback").unwrap();
+
+        assert_eq!(deck.name, "HSK / HSK 1");
+        assert_eq!(
+            deck.source.rows[0],
+            ["Hanzi", "Pinyin", "English", "Tags", "Known"]
+        );
+        let cards = build_report(&deck.source, &deck.mapping).cards();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].word, "苹果");
+        assert_eq!(cards[0].translation, "apple");
+        assert_eq!(cards[0].tags, ["hsk1", "food"]);
+        assert!(cards[0].known);
+        assert_eq!(cards[1].word, "你好");
+        assert_eq!(cards[1].translation, "hello & hi");
+        assert!(!cards[1].known);
+    }
+
+    #[test]
+    fn rejects_compressed_and_foreign_files() {
+        let error = read_apkg(&apkg("collection.anki21b"), "deck").err();
+        assert!(error.is_some_and(|error| error.contains("Support older Anki versions")));
+        assert!(read_apkg(b"word,translation\n", "deck").is_err());
+    }
+
+    #[test]
+    fn exports_text_for_anki_import() {
+        let deck = read_apkg(&apkg("collection.anki21"), "deck").unwrap();
+        let mut cards = build_report(&deck.source, &deck.mapping).cards();
+        cards[1].translation = "hello\tthere".to_string();
+
+        let text = String::from_utf8(export_anki_text("HSK / HSK 1", &cards).unwrap()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[3], "#deck:HSK::HSK 1");
+        assert_eq!(lines[6], "苹果\tpíngguǒ<br>apple\thsk1 food known");
+        assert_eq!(lines[7], "你好\t\"nǐ hǎo<br>hello\tthere\"\t");
+    }
+
+    #[test]
+    fn exports_one_column_per_note_type_field_and_tags() {
+        let cards = [crate::model::Flashcard {
+            word: "大<小>".to_string(),
+            pinyin: Some("dà".to_string()),
+            translation: "big & small".to_string(),
+            known: false,
+            tags: vec!["hsk1".to_string()],
+        }];
+        let text = String::from_utf8(export_anki_text("Deck", &cards).unwrap()).unwrap();
+        let columns = text
+            .lines()
+            .find_map(|line| line.strip_prefix("#columns:"))
+            .unwrap();
+        assert_eq!(columns, "Front\tBack\tTags");
+
+        let rows: Vec<_> = text.lines().filter(|line| !line.starts_with('#')).collect();
+        assert_eq!(rows, ["大&lt;小&gt;\tdà<br>big &amp; small\thsk1"]);
+        assert!(rows
+            .iter()
+            .all(|row| row.split('\t').count() == BASIC_FIELDS.len() + 1));
+    }
+
+    #[test]
+    fn strips_field_markup() {
+        assert_eq!(
+            field_text("<div>apple&nbsp;[sound:a.mp3]</div><br/>[fruit]"),
+            "apple [fruit]"
+        );
+    }
+}
@@ -1,22 +1,112 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use encoding_rs::Encoding;
+use gloo_events::EventListener;
 use gloo_file::callbacks::FileReader;
 use gloo_file::File;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use web_sys::{HtmlInputElement, InputEvent, MouseEvent};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement, InputEvent,
+    KeyboardEvent, MouseEvent, StorageEvent,
+};
 use yew::prelude::*;
 
+use crate::cedict::{cedict_text, parse_cedict, Dictionary, Reading};
 use crate::components::add_flashcard_form::AddFlashcardForm;
 use crate::components::dataset_panel::DatasetPanel;
+use crate::components::export_dialog::ExportDialog;
 use crate::components::flashcard_view::FlashcardView;
 use crate::components::help_panel::HelpPanel;
 use crate::components::known_cards_table::KnownCardsTable;
+use crate::components::print_dialog::PrintDialog;
+use crate::components::qr_dialog::QrDialog;
+use crate::components::qr_scanner::QrScanner;
+use crate::components::shared_wordset_preview::SharedWordsetPreview;
+use crate::components::snapshot_panel::SnapshotPanel;
 use crate::components::study_toolbar::StudyToolbar;
-use crate::csv_io::{export_flashcards_csv, parse_flashcards_from_csv, trigger_csv_download};
-use crate::model::{Dataset, Flashcard, FlashcardStage, PersistedState, StudyDirection};
-use crate::storage::{load_datasets, load_persisted_state, save_datasets, save_persisted_state};
+use crate::components::trash_panel::TrashPanel;
+use crate::csv_io::{trigger_csv_download, CsvDialect};
+use crate::export::{
+    default_file_stem, export_wordset, scoped_dataset, ExportOptions, ExportScope,
+};
+use crate::file_sync::{apply_sync, diff_with_file};
+use crate::history::{History, Snapshot};
+use crate::import::{
+    merge_cards, read_import_file, ImportTarget, ImportedFile, MergeStrategy, PendingImport,
+};
+use crate::model::{
+    ColumnField, Dataset, Flashcard, FlashcardStage, PersistedState, SnapshotKind, StudyDirection,
+    Trash, TrashedCard, TrashedDataset,
+};
+use crate::print::{open_print_view, print_sheet_html, PrintOptions};
+use crate::quizlet::QuizletSeparators;
+use crate::share::{
+    clear_share_fragment, current_page_url, share_link, shared_wordset_in_location,
+};
+use crate::snapshots::{push_snapshot, SnapshotSchedule};
+use crate::storage::{
+    clear_dictionary_text, load_datasets, load_dictionary_text, load_persisted_state,
+    load_snapshots, load_trash, save_datasets, save_dictionary_text, save_persisted_state,
+    save_snapshots, save_trash, stored_datasets_json, DATASETS_KEY, TRASH_KEY,
+};
+use crate::sync::merge_datasets;
+use crate::trash::{purge_expired, unique_dataset_name};
+use crate::validation::repair_state;
+
+/// Moves the next queued file into the import preview, sending it to the same
+/// target as the file before it.
+fn advance_import_queue(
+    import_queue: &UseStateHandle<Vec<PendingImport>>,
+    pending_import: &UseStateHandle<Option<PendingImport>>,
+    target: ImportTarget,
+) {
+    let mut queue = (**import_queue).clone();
+    let next = (!queue.is_empty())
+        .then(|| queue.remove(0))
+        .map(|mut next| {
+            next.set_target(target);
+            next
+        });
+    import_queue.set(queue);
+    pending_import.set(next);
+}
+
+/// Changes the trash with `purge` and drops the same items from the trash of every
+/// undo and redo entry. Purging is not recorded as undoable, since it is meant to
+/// delete the items for good.
+fn purge_trash(
+    trash: &UseStateHandle<Trash>,
+    history: &UseStateHandle<History>,
+    purge: impl Fn(&mut Trash),
+) {
+    let mut updated = (**trash).clone();
+    purge(&mut updated);
+    let mut updated_history = (**history).clone();
+    updated_history.edit_trash(purge);
+    history.set(updated_history);
+    trash.set(updated);
+}
 
-fn split_flashcards(cards: Vec<Flashcard>) -> (Vec<Flashcard>, Vec<Flashcard>) {
-    cards.into_iter().partition(|card| card.known)
+/// The active wordset with its live cards and the column mapping saved with it.
+fn active_dataset(
+    name: &str,
+    flashcards: &[Flashcard],
+    known_cards: &[Flashcard],
+    datasets: &[Dataset],
+) -> Dataset {
+    Dataset {
+        name: name.to_string(),
+        flashcards: flashcards.to_vec(),
+        known_cards: known_cards.to_vec(),
+        column_mapping: datasets
+            .iter()
+            .find(|dataset| dataset.name == name)
+            .and_then(|dataset| dataset.column_mapping.clone()),
+    }
 }
 
 fn display_text(card: &Flashcard, direction: StudyDirection, stage: FlashcardStage) -> String {
@@ -34,48 +124,36 @@ fn display_text(card: &Flashcard, direction: StudyDirection, stage: FlashcardSta
 
 #[function_component(App)]
 pub fn app() -> Html {
-    let persisted = load_persisted_state();
-
-    let flashcards = use_state(|| {
-        persisted
-            .as_ref()
-            .map(|state| state.flashcards.clone())
-            .unwrap_or_default()
-    });
-    let known_cards = use_state(|| {
-        persisted
-            .as_ref()
-            .map(|state| state.known_cards.clone())
-            .unwrap_or_default()
-    });
-    let current_index = use_state(|| {
-        persisted
-            .as_ref()
-            .map(|state| state.current_index)
-            .unwrap_or(0)
+    let loaded = use_memo((), |_| {
+        let mut persisted = load_persisted_state().unwrap_or_default();
+        let mut datasets = load_datasets();
+        let repairs = repair_state(&mut persisted, &mut datasets);
+        (persisted, datasets, repairs)
     });
-    let stage = use_state(|| {
-        persisted
-            .as_ref()
-            .map(|state| state.stage)
-            .unwrap_or_default()
-    });
-    let direction = use_state(|| {
-        persisted
-            .as_ref()
-            .map(|state| state.direction)
-            .unwrap_or_default()
-    });
-    let reader_handle = use_state(|| None::<FileReader>);
-
-    let datasets = load_datasets();
-    let current_dataset = use_state(|| {
-        persisted
-            .as_ref()
-            .map(|state| state.current_dataset.clone())
-            .unwrap_or_default()
-    });
-    let datasets_list = use_state(move || datasets);
+    let (persisted, datasets, repairs) = &*loaded;
+
+    let flashcards = use_state(|| persisted.flashcards.clone());
+    let known_cards = use_state(|| persisted.known_cards.clone());
+    let current_index = use_state(|| persisted.current_index);
+    let stage = use_state(|| persisted.stage);
+    let direction = use_state(|| persisted.direction);
+    let file_readers = use_mut_ref(Vec::<FileReader>::new);
+    let pending_import = use_state(|| None::<PendingImport>);
+    // Files dropped or chosen together, previewed one after another.
+    let import_queue = use_state(Vec::<PendingImport>::new);
+    let export_options = use_state(ExportOptions::default);
+    let show_export_dialog = use_state(|| false);
+    let export_share_link = use_state(|| None::<String>);
+    // A wordset from the share link the page was opened with, awaiting import.
+    let shared_wordset = use_state(|| None::<Dataset>);
+    let print_options = use_state(PrintOptions::default);
+    let show_print_dialog = use_state(|| false);
+    let qr_scope = use_state(ExportScope::default);
+    let show_qr_dialog = use_state(|| false);
+    let show_qr_scanner = use_state(|| false);
+
+    let current_dataset = use_state(|| persisted.current_dataset.clone());
+    let datasets_list = use_state(|| datasets.clone());
     let new_dataset_name = use_state(String::new);
     let show_dataset_input = use_state(|| false);
     let show_add = use_state(|| false);
@@ -83,9 +161,89 @@ pub fn app() -> Html {
     let new_word = use_state(String::new);
     let new_pinyin = use_state(String::new);
     let new_translation = use_state(String::new);
+    let dictionary = use_state(|| None::<Rc<Dictionary>>);
+    let dictionary_loading = use_state(|| false);
+    let dictionary_reader = use_state(|| None::<FileReader>);
+    // Whether the saved dictionary has been read from storage yet; it is only
+    // loaded once the add form is first opened.
+    let dictionary_requested = use_mut_ref(|| false);
+    // The reading last filled into the add form, so a later word can replace it
+    // as long as the user has not edited the fields.
+    let autofilled = use_mut_ref(|| None::<Reading>);
     let renaming_dataset = use_state(|| None::<String>);
     let rename_input = use_state(String::new);
     let show_unknown_in_table = use_state(|| false);
+    let history = use_state(History::default);
+    let trash = use_state(|| {
+        let mut trash = load_trash();
+        purge_expired(&mut trash, js_sys::Date::now());
+        trash
+    });
+    let show_trash = use_state(|| false);
+    // Wordsets as this tab last read them from or wrote them to storage.
+    let sync_base = use_mut_ref(load_datasets);
+    let stored_json = use_mut_ref(stored_datasets_json);
+    let notice = use_state(|| {
+        (!repairs.is_empty()).then(|| {
+            format!(
+                "Some saved data was inconsistent and has been repaired. {}",
+                repairs.join(" ")
+            )
+        })
+    });
+    let snapshots = use_state(Vec::new);
+    let show_snapshots = use_state(|| false);
+    // Unknown until the stored snapshots have been read.
+    let snapshot_schedule = use_mut_ref(|| None::<SnapshotSchedule>);
+
+    {
+        let dictionary = dictionary.clone();
+        let dictionary_loading = dictionary_loading.clone();
+        let dictionary_requested = dictionary_requested.clone();
+        use_effect_with(*show_add, move |visible| {
+            if *visible && !*dictionary_requested.borrow() {
+                *dictionary_requested.borrow_mut() = true;
+                dictionary_loading.set(true);
+                spawn_local(async move {
+                    if let Some(text) = load_dictionary_text().await {
+                        if let Ok(loaded) = parse_cedict(&text) {
+                            dictionary.set(Some(Rc::new(loaded)));
+                        }
+                    }
+                    dictionary_loading.set(false);
+                });
+            }
+            || ()
+        });
+    }
+
+    {
+        let notice = notice.clone();
+        let history = history.clone();
+        use_effect_with(trash.clone(), move |trash| {
+            let now = js_sys::Date::now();
+            if purge_expired(&mut (**trash).clone(), now) {
+                // Saved once the purged trash comes back through this effect.
+                purge_trash(trash, &history, |trash| {
+                    purge_expired(trash, now);
+                });
+            } else if let Err(error) = save_trash(trash) {
+                notice.set(Some(error));
+            }
+            || ()
+        });
+    }
+
+    {
+        let snapshot_schedule = snapshot_schedule.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                let stored = load_snapshots().await;
+                *snapshot_schedule.borrow_mut() = Some(SnapshotSchedule::from_snapshots(&stored));
+            });
+            || ()
+        });
+    }
 
     {
         let flashcards = flashcards.clone();
@@ -94,6 +252,7 @@ pub fn app() -> Html {
         let stage = stage.clone();
         let direction = direction.clone();
         let current_dataset = current_dataset.clone();
+        let notice = notice.clone();
 
         use_effect_with(
             (
@@ -105,7 +264,7 @@ pub fn app() -> Html {
                 current_dataset.clone(),
             ),
             move |_| {
-                save_persisted_state(&PersistedState {
+                let saved = save_persisted_state(&PersistedState {
                     flashcards: (*flashcards).clone(),
                     known_cards: (*known_cards).clone(),
                     current_index: *current_index,
@@ -113,6 +272,9 @@ pub fn app() -> Html {
                     direction: *direction,
                     current_dataset: (*current_dataset).clone(),
                 });
+                if let Err(error) = saved {
+                    notice.set(Some(error));
+                }
                 || ()
             },
         );
@@ -139,8 +301,7 @@ pub fn app() -> Html {
                     {
                         dataset.flashcards = (*flashcards).clone();
                         dataset.known_cards = (*known_cards).clone();
-                        datasets_list.set(datasets.clone());
-                        save_datasets(&datasets);
+                        datasets_list.set(datasets);
                     }
                 }
                 || ()
@@ -148,6 +309,304 @@ pub fn app() -> Html {
         );
     }
 
+    let replace_datasets = {
+        let datasets_list = datasets_list.clone();
+        let current_dataset = current_dataset.clone();
+        let flashcards = flashcards.clone();
+        let known_cards = known_cards.clone();
+        let current_index = current_index.clone();
+        let stage = stage.clone();
+
+        Callback::from(move |datasets: Vec<Dataset>| {
+            match datasets
+                .iter()
+                .find(|dataset| dataset.name == *current_dataset)
+            {
+                Some(dataset)
+                    if dataset.flashcards != *flashcards || dataset.known_cards != *known_cards =>
+                {
+                    flashcards.set(dataset.flashcards.clone());
+                    known_cards.set(dataset.known_cards.clone());
+                    if *current_index >= dataset.flashcards.len() {
+                        current_index.set(0);
+                    }
+                    stage.set(FlashcardStage::First);
+                }
+                Some(_) => {}
+                None if !current_dataset.is_empty() => {
+                    current_dataset.set(String::new());
+                    flashcards.set(Vec::new());
+                    known_cards.set(Vec::new());
+                    current_index.set(0);
+                    stage.set(FlashcardStage::First);
+                }
+                None => {}
+            }
+            datasets_list.set(datasets);
+        })
+    };
+
+    let sync_from_storage = {
+        let sync_base = sync_base.clone();
+        let stored_json = stored_json.clone();
+        let notice = notice.clone();
+        let datasets_list = datasets_list.clone();
+        let replace_datasets = replace_datasets.clone();
+
+        Callback::from(move |_: ()| {
+            let stored = load_datasets();
+            *stored_json.borrow_mut() = stored_datasets_json();
+            let outcome = merge_datasets(&sync_base.borrow(), &datasets_list, &stored);
+            if outcome.datasets != stored {
+                match save_datasets(&outcome.datasets) {
+                    Ok(()) => *stored_json.borrow_mut() = stored_datasets_json(),
+                    Err(error) => notice.set(Some(error)),
+                }
+            }
+            *sync_base.borrow_mut() = outcome.datasets.clone();
+
+            if !outcome.conflicts.is_empty() {
+                notice.set(Some(format!(
+                    "{} changed in another tab at the same time. Both versions were kept; the other tab's copy is marked \"(other tab)\".",
+                    outcome.conflicts.join(", ")
+                )));
+            }
+
+            if outcome.datasets != *datasets_list {
+                replace_datasets.emit(outcome.datasets);
+            }
+        })
+    };
+
+    {
+        let sync_base = sync_base.clone();
+        let stored_json = stored_json.clone();
+        let sync_from_storage = sync_from_storage.clone();
+        let snapshot_schedule = snapshot_schedule.clone();
+        let notice = notice.clone();
+
+        use_effect_with(datasets_list.clone(), move |datasets_list| {
+            if stored_datasets_json() != *stored_json.borrow() {
+                sync_from_storage.emit(());
+            } else if **datasets_list != *sync_base.borrow() {
+                match save_datasets(datasets_list) {
+                    Ok(()) => {
+                        *sync_base.borrow_mut() = (**datasets_list).clone();
+                        *stored_json.borrow_mut() = stored_datasets_json();
+                    }
+                    Err(error) => notice.set(Some(error)),
+                }
+            }
+
+            let now = js_sys::Date::now();
+            let due = snapshot_schedule
+                .borrow_mut()
+                .as_mut()
+                .map(|schedule| schedule.take_due(datasets_list, now))
+                .unwrap_or_default();
+            if !due.is_empty() {
+                // Snapshots taken at the same moment share one copy of the wordsets.
+                let datasets = Rc::new((**datasets_list).clone());
+                spawn_local(async move {
+                    let mut stored = load_snapshots().await;
+                    for kind in due {
+                        push_snapshot(&mut stored, kind, datasets.clone(), now);
+                    }
+                    if let Err(error) = save_snapshots(&stored).await {
+                        notice.set(Some(error));
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    {
+        let sync_from_storage = sync_from_storage.clone();
+        let trash = trash.clone();
+
+        use_effect(move || {
+            let listener = web_sys::window().map(|window| {
+                EventListener::new(&window, "storage", move |event| {
+                    let Some(event) = event.dyn_ref::<StorageEvent>() else {
+                        return;
+                    };
+                    match event.key().as_deref() {
+                        Some(DATASETS_KEY) | None => sync_from_storage.emit(()),
+                        Some(TRASH_KEY) => trash.set(load_trash()),
+                        Some(_) => {}
+                    }
+                })
+            });
+            move || drop(listener)
+        });
+    }
+
+    {
+        let shared_wordset = shared_wordset.clone();
+        let notice = notice.clone();
+
+        use_effect_with((), move |_| {
+            let open_shared_link = move || {
+                match shared_wordset_in_location() {
+                    Some(Ok(dataset)) => shared_wordset.set(Some(dataset)),
+                    Some(Err(error)) => notice.set(Some(format!(
+                        "The shared wordset could not be opened. {error}"
+                    ))),
+                    None => return,
+                }
+                let _ = clear_share_fragment();
+            };
+            open_shared_link();
+            // A link pasted into the address bar of an open tab only changes the fragment.
+            let listener = web_sys::window().map(|window| {
+                EventListener::new(&window, "hashchange", move |_| open_shared_link())
+            });
+            move || drop(listener)
+        });
+    }
+
+    let take_snapshot = {
+        let trash = trash.clone();
+        let datasets_list = datasets_list.clone();
+        let current_dataset = current_dataset.clone();
+        let flashcards = flashcards.clone();
+        let known_cards = known_cards.clone();
+        let current_index = current_index.clone();
+
+        move || {
+            let mut datasets = (*datasets_list).clone();
+            if let Some(dataset) = datasets
+                .iter_mut()
+                .find(|dataset| dataset.name == *current_dataset)
+            {
+                dataset.flashcards = (*flashcards).clone();
+                dataset.known_cards = (*known_cards).clone();
+            }
+            Snapshot {
+                datasets,
+                current_dataset: (*current_dataset).clone(),
+                flashcards: (*flashcards).clone(),
+                known_cards: (*known_cards).clone(),
+                current_index: *current_index,
+                trash: (*trash).clone(),
+            }
+        }
+    };
+
+    let record_history = {
+        let history = history.clone();
+        let take_snapshot = take_snapshot.clone();
+
+        Callback::from(move |label: &'static str| {
+            let mut updated = (*history).clone();
+            updated.record(label, take_snapshot());
+            history.set(updated);
+        })
+    };
+
+    let apply_snapshot = {
+        let trash = trash.clone();
+        let datasets_list = datasets_list.clone();
+        let current_dataset = current_dataset.clone();
+        let flashcards = flashcards.clone();
+        let known_cards = known_cards.clone();
+        let current_index = current_index.clone();
+        let stage = stage.clone();
+
+        move |snapshot: Snapshot| {
+            datasets_list.set(snapshot.datasets);
+            current_dataset.set(snapshot.current_dataset);
+            flashcards.set(snapshot.flashcards);
+            known_cards.set(snapshot.known_cards);
+            current_index.set(snapshot.current_index);
+            trash.set(snapshot.trash);
+            stage.set(FlashcardStage::First);
+        }
+    };
+
+    let undo = {
+        let history = history.clone();
+        let take_snapshot = take_snapshot.clone();
+        let apply_snapshot = apply_snapshot.clone();
+
+        Callback::from(move |_: ()| {
+            let mut updated = (*history).clone();
+            if let Some(snapshot) = updated.undo(take_snapshot()) {
+                apply_snapshot(snapshot);
+                history.set(updated);
+            }
+        })
+    };
+
+    let redo = {
+        let history = history.clone();
+        let take_snapshot = take_snapshot.clone();
+        let apply_snapshot = apply_snapshot.clone();
+
+        Callback::from(move |_: ()| {
+            let mut updated = (*history).clone();
+            if let Some(snapshot) = updated.redo(take_snapshot()) {
+                apply_snapshot(snapshot);
+                history.set(updated);
+            }
+        })
+    };
+
+    {
+        let undo = undo.clone();
+        let redo = redo.clone();
+
+        use_effect(move || {
+            let listener = web_sys::window().map(|window| {
+                EventListener::new(&window, "keydown", move |event| {
+                    let Some(event) = event.dyn_ref::<KeyboardEvent>() else {
+                        return;
+                    };
+                    if !(event.ctrl_key() || event.meta_key())
+                        || !event.key().eq_ignore_ascii_case("z")
+                    {
+                        return;
+                    }
+                    // Form fields keep their own undo for the text typed into them.
+                    let editing_text = event.target().is_some_and(|target| {
+                        target.has_type::<HtmlInputElement>()
+                            || target.has_type::<HtmlTextAreaElement>()
+                            || target.has_type::<HtmlSelectElement>()
+                            || target
+                                .dyn_ref::<HtmlElement>()
+                                .is_some_and(HtmlElement::is_content_editable)
+                    });
+                    if editing_text {
+                        return;
+                    }
+                    event.prevent_default();
+                    if event.shift_key() {
+                        redo.emit(());
+                    } else {
+                        undo.emit(());
+                    }
+                })
+            });
+            move || drop(listener)
+        });
+    }
+
+    let move_card_to_trash = {
+        let trash = trash.clone();
+        let current_dataset = current_dataset.clone();
+
+        Callback::from(move |card: Flashcard| {
+            let mut updated = (*trash).clone();
+            updated.cards.push(TrashedCard {
+                card,
+                dataset: (*current_dataset).clone(),
+                deleted_at: js_sys::Date::now(),
+            });
+            trash.set(updated);
+        })
+    };
+
     let load_dataset = {
         let datasets_list = datasets_list.clone();
         let current_dataset = current_dataset.clone();
@@ -168,6 +627,7 @@ pub fn app() -> Html {
     };
 
     let add_new_dataset = {
+        let record_history = record_history.clone();
         let new_dataset_name = new_dataset_name.clone();
         let datasets_list = datasets_list.clone();
         let current_dataset = current_dataset.clone();
@@ -184,18 +644,19 @@ pub fn app() -> Html {
                     .iter()
                     .any(|dataset| dataset.name == *new_dataset_name)
                 {
+                    record_history.emit("Create wordset");
                     datasets.push(Dataset {
                         name: (*new_dataset_name).clone(),
                         flashcards: Vec::new(),
                         known_cards: Vec::new(),
+                        column_mapping: None,
                     });
-                    datasets_list.set(datasets.clone());
+                    datasets_list.set(datasets);
                     current_dataset.set((*new_dataset_name).clone());
                     flashcards.set(Vec::new());
                     known_cards.set(Vec::new());
                     current_index.set(0);
                     stage.set(FlashcardStage::First);
-                    save_datasets(&datasets);
                 }
                 new_dataset_name.set(String::new());
                 show_dataset_input.set(false);
@@ -213,6 +674,8 @@ pub fn app() -> Html {
     };
 
     let delete_dataset = {
+        let record_history = record_history.clone();
+        let trash = trash.clone();
         let datasets_list = datasets_list.clone();
         let current_dataset = current_dataset.clone();
         let flashcards = flashcards.clone();
@@ -222,9 +685,17 @@ pub fn app() -> Html {
 
         Callback::from(move |name: String| {
             let mut datasets = (*datasets_list).clone();
-            datasets.retain(|dataset| dataset.name != name);
-            datasets_list.set(datasets.clone());
-            save_datasets(&datasets);
+            let Some(position) = datasets.iter().position(|dataset| dataset.name == name) else {
+                return;
+            };
+            record_history.emit("Delete wordset");
+            let mut updated_trash = (*trash).clone();
+            updated_trash.datasets.push(TrashedDataset {
+                dataset: datasets.remove(position),
+                deleted_at: js_sys::Date::now(),
+            });
+            trash.set(updated_trash);
+            datasets_list.set(datasets);
 
             if *current_dataset == name {
                 current_dataset.set(String::new());
@@ -255,6 +726,7 @@ pub fn app() -> Html {
     };
 
     let confirm_rename = {
+        let record_history = record_history.clone();
         let renaming_dataset = renaming_dataset.clone();
         let rename_input = rename_input.clone();
         let datasets_list = datasets_list.clone();
@@ -271,14 +743,14 @@ pub fn app() -> Html {
             if new_name != old_name && datasets.iter().any(|d| d.name == new_name) {
                 return;
             }
+            record_history.emit("Rename wordset");
             if let Some(dataset) = datasets.iter_mut().find(|d| d.name == old_name) {
                 dataset.name = new_name.clone();
             }
             if *current_dataset == old_name {
                 current_dataset.set(new_name.clone());
             }
-            datasets_list.set(datasets.clone());
-            save_datasets(&datasets);
+            datasets_list.set(datasets);
             renaming_dataset.set(None);
             rename_input.set(String::new());
         })
@@ -293,39 +765,372 @@ pub fn app() -> Html {
         })
     };
 
-    let on_file_select = {
-        let flashcards = flashcards.clone();
-        let known_cards = known_cards.clone();
-        let reader_handle = reader_handle.clone();
+    let import_files = {
+        let record_history = record_history.clone();
+        let datasets_list = datasets_list.clone();
+        let current_dataset = current_dataset.clone();
+        let notice = notice.clone();
+        let pending_import = pending_import.clone();
+        let import_queue = import_queue.clone();
+        let file_readers = file_readers.clone();
 
+        Callback::from(move |files: Vec<web_sys::File>| {
+            if files.is_empty() {
+                return;
+            }
+            let remembered = datasets_list
+                .iter()
+                .find(|dataset| dataset.name == *current_dataset)
+                .and_then(|dataset| dataset.column_mapping.clone());
+            // Several files each get a wordset of their own unless the user
+            // switches them back to the current wordset in the preview.
+            let target = if files.len() > 1 || current_dataset.is_empty() {
+                ImportTarget::NewWordset
+            } else {
+                ImportTarget::CurrentWordset
+            };
+            let results = Rc::new(RefCell::new(files.iter().map(|_| None).collect::<Vec<_>>()));
+
+            let mut readers = file_readers.borrow_mut();
+            readers.clear();
+            for (index, file) in files.into_iter().enumerate() {
+                let file = File::from(file);
+                let file_name = file.name();
+                let remembered = remembered.clone();
+                let results = results.clone();
+                let record_history = record_history.clone();
+                let datasets_list = datasets_list.clone();
+                let notice = notice.clone();
+                let pending_import = pending_import.clone();
+                let import_queue = import_queue.clone();
+
+                readers.push(gloo_file::callbacks::read_as_bytes(&file, move |result| {
+                    let imported = result
+                        .map_err(|error| error.to_string())
+                        .and_then(|bytes| {
+                            read_import_file(file_name.clone(), bytes, remembered.as_ref())
+                        })
+                        .map_err(|error| format!("{file_name}: {error}"));
+                    results.borrow_mut()[index] = Some(imported);
+                    if results.borrow().iter().any(Option::is_none) {
+                        return;
+                    }
+
+                    let mut messages = Vec::new();
+                    let mut previews = Vec::new();
+                    let mut wordsets = Vec::new();
+                    for imported in results.borrow_mut().drain(..).flatten() {
+                        match imported {
+                            Ok(ImportedFile::Rows(mut pending)) => {
+                                pending.set_target(target);
+                                previews.push(pending);
+                            }
+                            Ok(ImportedFile::Wordset(dataset)) => wordsets.push(dataset),
+                            Err(error) => messages.push(error),
+                        }
+                    }
+                    if !wordsets.is_empty() {
+                        record_history.emit("Import wordsets");
+                        let mut datasets = (*datasets_list).clone();
+                        for mut dataset in wordsets {
+                            dataset.name =
+                                unique_dataset_name(&dataset.name, "imported", &datasets);
+                            messages.push(format!(
+                                "Added wordset \"{}\" with {} cards.",
+                                dataset.name,
+                                dataset.flashcards.len() + dataset.known_cards.len()
+                            ));
+                            datasets.push(dataset);
+                        }
+                        datasets_list.set(datasets);
+                    }
+                    if !messages.is_empty() {
+                        notice.set(Some(messages.join(" ")));
+                    }
+                    let mut previews = previews.into_iter();
+                    pending_import.set(previews.next());
+                    import_queue.set(previews.collect());
+                }));
+            }
+        })
+    };
+
+    let on_file_select = {
+        let import_files = import_files.clone();
         Callback::from(move |event: Event| {
             let Some(input) = event.target_dyn_into::<HtmlInputElement>() else {
                 return;
             };
-
             let Some(files) = input.files() else {
                 return;
             };
+            import_files.emit(
+                (0..files.length())
+                    .filter_map(|index| files.get(index))
+                    .collect(),
+            );
+            // Allow choosing the same file again after cancelling its import.
+            input.set_value("");
+        })
+    };
 
-            let Some(file) = files.get(0) else {
+    let import_pasted_text = {
+        let datasets_list = datasets_list.clone();
+        let current_dataset = current_dataset.clone();
+        let pending_import = pending_import.clone();
+        let import_queue = import_queue.clone();
+
+        Callback::from(move |text: String| {
+            if text.trim().is_empty() {
+                return;
+            }
+            let remembered = datasets_list
+                .iter()
+                .find(|dataset| dataset.name == *current_dataset)
+                .and_then(|dataset| dataset.column_mapping.clone());
+            let mut pending = PendingImport::text_file(
+                "Pasted text".to_string(),
+                text.into_bytes(),
+                remembered.as_ref(),
+            );
+            if current_dataset.is_empty() {
+                pending.set_target(ImportTarget::NewWordset);
+            }
+            pending_import.set(Some(pending));
+            import_queue.set(Vec::new());
+        })
+    };
+
+    let change_import_column = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |(column, field): (usize, ColumnField)| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                if let Some(slot) = pending.mapping.columns.get_mut(column) {
+                    *slot = field;
+                }
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_import_header = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |has_header: bool| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.mapping.has_header = has_header;
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_import_dialect = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |dialect: CsvDialect| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.set_dialect(dialect);
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_import_encoding = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |encoding: &'static Encoding| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.set_encoding(encoding);
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_export_options = {
+        let export_options = export_options.clone();
+        let export_share_link = export_share_link.clone();
+        Callback::from(move |options: ExportOptions| {
+            export_share_link.set(None);
+            export_options.set(options);
+        })
+    };
+
+    let change_import_split = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |split: bool| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.set_split_categories(split);
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_import_quizlet = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |separators: Option<QuizletSeparators>| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.set_quizlet(separators);
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_import_pleco = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |pleco: bool| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.set_pleco(pleco);
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_import_strategy = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |strategy: MergeStrategy| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.strategy = strategy;
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_import_target = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |target: ImportTarget| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.set_target(target);
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let change_import_sheet = {
+        let pending_import = pending_import.clone();
+        Callback::from(move |index: usize| {
+            if let Some(mut pending) = (*pending_import).clone() {
+                pending.set_sheet(index);
+                pending_import.set(Some(pending));
+            }
+        })
+    };
+
+    let cancel_import = {
+        let pending_import = pending_import.clone();
+        let import_queue = import_queue.clone();
+        Callback::from(move |_: MouseEvent| {
+            let target = pending_import
+                .as_ref()
+                .map_or(ImportTarget::default(), |pending| pending.target);
+            advance_import_queue(&import_queue, &pending_import, target);
+        })
+    };
+
+    let confirm_import = {
+        let record_history = record_history.clone();
+        let notice = notice.clone();
+        let pending_import = pending_import.clone();
+        let import_queue = import_queue.clone();
+        let datasets_list = datasets_list.clone();
+        let current_dataset = current_dataset.clone();
+        let flashcards = flashcards.clone();
+        let known_cards = known_cards.clone();
+        let current_index = current_index.clone();
+        let stage = stage.clone();
+        let trash = trash.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let Some(pending) = (*pending_import).clone() else {
                 return;
             };
+            advance_import_queue(&import_queue, &pending_import, pending.target);
 
-            let file = File::from(file);
-            let flashcards = flashcards.clone();
-            let known_cards = known_cards.clone();
-            let reader_handle = reader_handle.clone();
-
-            let task = gloo_file::callbacks::read_as_text(&file, move |result| {
-                if let Ok(csv_data) = result {
-                    let all_cards = parse_flashcards_from_csv(&csv_data);
-                    let (known, unknown) = split_flashcards(all_cards);
-                    flashcards.set(unknown);
-                    known_cards.set(known);
+            if let Some(wordsets) = pending.new_wordsets() {
+                record_history.emit("Import wordsets");
+                let mut datasets = (*datasets_list).clone();
+                let mut created = Vec::new();
+                for (name, cards) in wordsets {
+                    let name = unique_dataset_name(&name, "imported", &datasets);
+                    let (known, unknown): (Vec<_>, Vec<_>) =
+                        cards.into_iter().partition(|card| card.known);
+                    created.push(format!(
+                        "\"{name}\" ({} cards)",
+                        unknown.len() + known.len()
+                    ));
+                    datasets.push(Dataset {
+                        name,
+                        flashcards: unknown,
+                        known_cards: known,
+                        column_mapping: pending
+                            .has_target_choice()
+                            .then(|| pending.mapping.clone()),
+                    });
                 }
-            });
+                notice.set(Some(format!(
+                    "Created {} from {}.",
+                    created.join(", "),
+                    pending.file_name
+                )));
+                if let Some(first) = datasets.get(datasets.len() - created.len()) {
+                    current_dataset.set(first.name.clone());
+                    flashcards.set(first.flashcards.clone());
+                    known_cards.set(first.known_cards.clone());
+                    current_index.set(0);
+                    stage.set(FlashcardStage::First);
+                }
+                datasets_list.set(datasets);
+                return;
+            }
+
+            let imported = pending.cards();
+            let mut unknown = (*flashcards).clone();
+            let mut known = (*known_cards).clone();
+            let was_empty = unknown.is_empty() && known.is_empty();
+            if pending.strategy == MergeStrategy::SyncWithFile && !was_empty {
+                record_history.emit("Sync with file");
+                let current: Vec<_> = unknown.iter().chain(&known).cloned().collect();
+                let diff = diff_with_file(&current, &imported, &pending.mapping.columns);
+                apply_sync(&mut unknown, &mut known, &diff);
+                let deleted_at = js_sys::Date::now();
+                let mut updated_trash = (*trash).clone();
+                updated_trash
+                    .cards
+                    .extend(diff.removed.iter().map(|card| TrashedCard {
+                        card: card.clone(),
+                        dataset: (*current_dataset).clone(),
+                        deleted_at,
+                    }));
+                trash.set(updated_trash);
+                notice.set(Some(format!(
+                    "Synced \"{}\" with {}: {}.",
+                    *current_dataset,
+                    pending.file_name,
+                    diff.describe()
+                )));
+            } else {
+                record_history.emit("Import cards");
+                let summary = merge_cards(&mut unknown, &mut known, imported, pending.strategy);
+                if !was_empty {
+                    notice.set(Some(format!(
+                        "Imported {} into \"{}\": {}.",
+                        pending.file_name,
+                        *current_dataset,
+                        summary.describe()
+                    )));
+                }
+            }
 
-            reader_handle.set(Some(task));
+            let mut datasets = (*datasets_list).clone();
+            if let Some(dataset) = datasets
+                .iter_mut()
+                .find(|dataset| dataset.name == *current_dataset)
+            {
+                dataset.column_mapping = Some(pending.mapping);
+            }
+            datasets_list.set(datasets);
+            if *current_index >= unknown.len() {
+                current_index.set(0);
+            }
+            flashcards.set(unknown);
+            known_cards.set(known);
+            stage.set(FlashcardStage::First);
         })
     };
 
@@ -341,16 +1146,18 @@ pub fn app() -> Html {
     };
 
     let mark_known = {
+        let record_history = record_history.clone();
         let flashcards = flashcards.clone();
         let known_cards = known_cards.clone();
         let current_index = current_index.clone();
         let stage = stage.clone();
 
         Callback::from(move |_: MouseEvent| {
-            if flashcards.is_empty() {
+            if *current_index >= flashcards.len() {
                 return;
             }
 
+            record_history.emit("Mark card as known");
             let mut list = (*flashcards).clone();
             let mut card = list.remove(*current_index);
             card.known = true;
@@ -377,12 +1184,14 @@ pub fn app() -> Html {
     };
 
     let restore_card = {
+        let record_history = record_history.clone();
         let flashcards = flashcards.clone();
         let known_cards = known_cards.clone();
 
         Callback::from(move |index: usize| {
             let mut known = (*known_cards).clone();
             if index < known.len() {
+                record_history.emit("Restore card");
                 let mut card = known.remove(index);
                 card.known = false;
 
@@ -395,17 +1204,20 @@ pub fn app() -> Html {
     };
 
     let delete_flashcard = {
+        let record_history = record_history.clone();
+        let move_card_to_trash = move_card_to_trash.clone();
         let flashcards = flashcards.clone();
         let current_index = current_index.clone();
         let stage = stage.clone();
 
         Callback::from(move |_: MouseEvent| {
-            if flashcards.is_empty() {
+            if *current_index >= flashcards.len() {
                 return;
             }
 
+            record_history.emit("Delete card");
             let mut list = (*flashcards).clone();
-            list.remove(*current_index);
+            move_card_to_trash.emit(list.remove(*current_index));
 
             if list.is_empty() {
                 flashcards.set(Vec::new());
@@ -425,12 +1237,15 @@ pub fn app() -> Html {
     };
 
     let delete_known_card = {
+        let record_history = record_history.clone();
+        let move_card_to_trash = move_card_to_trash.clone();
         let known_cards = known_cards.clone();
 
         Callback::from(move |index: usize| {
             let mut known = (*known_cards).clone();
             if index < known.len() {
-                known.remove(index);
+                record_history.emit("Delete card");
+                move_card_to_trash.emit(known.remove(index));
                 known_cards.set(known);
             }
         })
@@ -444,6 +1259,7 @@ pub fn app() -> Html {
     };
 
     let mark_known_from_table = {
+        let record_history = record_history.clone();
         let flashcards = flashcards.clone();
         let known_cards = known_cards.clone();
         let current_index = current_index.clone();
@@ -451,6 +1267,7 @@ pub fn app() -> Html {
         Callback::from(move |index: usize| {
             let mut list = (*flashcards).clone();
             if index < list.len() {
+                record_history.emit("Mark card as known");
                 let mut card = list.remove(index);
                 card.known = true;
                 let mut known = (*known_cards).clone();
@@ -474,13 +1291,16 @@ pub fn app() -> Html {
     };
 
     let delete_unknown_from_table = {
+        let record_history = record_history.clone();
+        let move_card_to_trash = move_card_to_trash.clone();
         let flashcards = flashcards.clone();
         let current_index = current_index.clone();
         let stage = stage.clone();
         Callback::from(move |index: usize| {
             let mut list = (*flashcards).clone();
             if index < list.len() {
-                list.remove(index);
+                record_history.emit("Delete card");
+                move_card_to_trash.emit(list.remove(index));
                 if list.is_empty() {
                     flashcards.set(Vec::new());
                     current_index.set(0);
@@ -543,11 +1363,13 @@ pub fn app() -> Html {
     };
 
     let randomize_cards = {
+        let record_history = record_history.clone();
         let flashcards = flashcards.clone();
         let current_index = current_index.clone();
         let stage = stage.clone();
 
         Callback::from(move |_: MouseEvent| {
+            record_history.emit("Shuffle cards");
             let mut shuffled = (*flashcards).clone();
             let mut rng = thread_rng();
             shuffled.shuffle(&mut rng);
@@ -579,10 +1401,108 @@ pub fn app() -> Html {
 
     let oninput_new_word = {
         let new_word = new_word.clone();
+        let new_pinyin = new_pinyin.clone();
+        let new_translation = new_translation.clone();
+        let dictionary = dictionary.clone();
+        let autofilled = autofilled.clone();
         Callback::from(move |event: InputEvent| {
-            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
-                new_word.set(input.value());
+            let Some(input) = event.target_dyn_into::<HtmlInputElement>() else {
+                return;
+            };
+            let word = input.value();
+            let untouched = match &*autofilled.borrow() {
+                Some(reading) => {
+                    reading.pinyin == *new_pinyin && reading.translation == *new_translation
+                }
+                None => new_pinyin.is_empty() && new_translation.is_empty(),
+            };
+            if untouched {
+                let readings = dictionary
+                    .as_ref()
+                    .map(|dictionary| dictionary.lookup(&word))
+                    .unwrap_or_default();
+                let fill = match readings {
+                    [reading] => Some(reading.clone()),
+                    _ => None,
+                };
+                new_pinyin.set(fill.as_ref().map(|r| r.pinyin.clone()).unwrap_or_default());
+                new_translation.set(
+                    fill.as_ref()
+                        .map(|r| r.translation.clone())
+                        .unwrap_or_default(),
+                );
+                *autofilled.borrow_mut() = fill;
             }
+            new_word.set(word);
+        })
+    };
+
+    let pick_reading = {
+        let new_pinyin = new_pinyin.clone();
+        let new_translation = new_translation.clone();
+        let autofilled = autofilled.clone();
+        Callback::from(move |reading: Reading| {
+            new_pinyin.set(reading.pinyin.clone());
+            new_translation.set(reading.translation.clone());
+            *autofilled.borrow_mut() = Some(reading);
+        })
+    };
+
+    let on_dictionary_select = {
+        let dictionary = dictionary.clone();
+        let dictionary_loading = dictionary_loading.clone();
+        let dictionary_reader = dictionary_reader.clone();
+        let notice = notice.clone();
+
+        Callback::from(move |event: Event| {
+            let Some(file) = event
+                .target_dyn_into::<HtmlInputElement>()
+                .and_then(|input| input.files())
+                .and_then(|files| files.get(0))
+            else {
+                return;
+            };
+
+            let file = File::from(file);
+            let file_name = file.name();
+            let dictionary = dictionary.clone();
+            let dictionary_loading = dictionary_loading.clone();
+            let notice = notice.clone();
+            dictionary_loading.set(true);
+
+            let task = gloo_file::callbacks::read_as_bytes(&file, move |result| {
+                let loaded = result
+                    .map_err(|error| error.to_string())
+                    .and_then(|bytes| cedict_text(&file_name, &bytes))
+                    .and_then(|text| parse_cedict(&text).map(|loaded| (loaded, text)));
+                match loaded {
+                    Ok((loaded, text)) => {
+                        notice.set(Some(format!(
+                            "Loaded {} dictionary entries from {file_name}.",
+                            loaded.entry_count()
+                        )));
+                        dictionary.set(Some(Rc::new(loaded)));
+                        let notice = notice.clone();
+                        spawn_local(async move {
+                            if let Err(error) = save_dictionary_text(&text).await {
+                                notice.set(Some(error));
+                            }
+                        });
+                    }
+                    Err(error) => notice.set(Some(error)),
+                }
+                dictionary_loading.set(false);
+            });
+
+            dictionary_reader.set(Some(task));
+        })
+    };
+
+    let remove_dictionary = {
+        let dictionary = dictionary.clone();
+        Callback::from(move |_: MouseEvent| {
+            dictionary.set(None);
+            spawn_local(clear_dictionary_text());
         })
     };
 
@@ -605,13 +1525,16 @@ pub fn app() -> Html {
     };
 
     let save_new = {
+        let record_history = record_history.clone();
         let flashcards = flashcards.clone();
         let new_word = new_word.clone();
         let new_pinyin = new_pinyin.clone();
         let new_translation = new_translation.clone();
+        let autofilled = autofilled.clone();
         let show_add = show_add.clone();
 
         Callback::from(move |_: MouseEvent| {
+            record_history.emit("Add card");
             let mut list = (*flashcards).clone();
             let pinyin = if new_pinyin.is_empty() {
                 None
@@ -624,32 +1547,444 @@ pub fn app() -> Html {
                 pinyin,
                 translation: (*new_translation).clone(),
                 known: false,
+                tags: Vec::new(),
             });
 
             flashcards.set(list);
             new_word.set(String::new());
             new_pinyin.set(String::new());
             new_translation.set(String::new());
+            *autofilled.borrow_mut() = None;
             show_add.set(false);
         })
     };
 
+    let open_trash = {
+        let show_trash = show_trash.clone();
+        Callback::from(move |_: MouseEvent| show_trash.set(true))
+    };
+
+    let close_trash = {
+        let show_trash = show_trash.clone();
+        Callback::from(move |_: MouseEvent| show_trash.set(false))
+    };
+
+    let restore_dataset_from_trash = {
+        let record_history = record_history.clone();
+        let trash = trash.clone();
+        let datasets_list = datasets_list.clone();
+
+        Callback::from(move |index: usize| {
+            let mut updated_trash = (*trash).clone();
+            if index >= updated_trash.datasets.len() {
+                return;
+            }
+            record_history.emit("Restore wordset from trash");
+            let mut dataset = updated_trash.datasets.remove(index).dataset;
+            let mut datasets = (*datasets_list).clone();
+            dataset.name = unique_dataset_name(&dataset.name, "restored", &datasets);
+            datasets.push(dataset);
+            datasets_list.set(datasets);
+            trash.set(updated_trash);
+        })
+    };
+
+    let restore_card_from_trash = {
+        let record_history = record_history.clone();
+        let trash = trash.clone();
+        let datasets_list = datasets_list.clone();
+        let current_dataset = current_dataset.clone();
+        let flashcards = flashcards.clone();
+        let known_cards = known_cards.clone();
+
+        Callback::from(move |index: usize| {
+            let mut updated_trash = (*trash).clone();
+            let Some(item) = updated_trash.cards.get(index) else {
+                return;
+            };
+            // Cards whose wordset no longer exists go to the active wordset instead.
+            let target = if datasets_list
+                .iter()
+                .any(|dataset| dataset.name == item.dataset)
+            {
+                item.dataset.clone()
+            } else if !current_dataset.is_empty() {
+                (*current_dataset).clone()
+            } else {
+                return;
+            };
+            record_history.emit("Restore card from trash");
+            let card = updated_trash.cards.remove(index).card;
+            trash.set(updated_trash);
+
+            if target == *current_dataset {
+                let list = if card.known {
+                    &known_cards
+                } else {
+                    &flashcards
+                };
+                let mut cards = (**list).clone();
+                cards.push(card);
+                list.set(cards);
+            } else {
+                let mut datasets = (*datasets_list).clone();
+                if let Some(dataset) = datasets.iter_mut().find(|dataset| dataset.name == target) {
+                    if card.known {
+                        dataset.known_cards.push(card);
+                    } else {
+                        dataset.flashcards.push(card);
+                    }
+                }
+                datasets_list.set(datasets);
+            }
+        })
+    };
+
+    let purge_dataset_from_trash = {
+        let history = history.clone();
+        let trash = trash.clone();
+
+        Callback::from(move |index: usize| {
+            if let Some(item) = trash.datasets.get(index).cloned() {
+                purge_trash(&trash, &history, |trash| {
+                    trash.datasets.retain(|existing| *existing != item);
+                });
+            }
+        })
+    };
+
+    let purge_card_from_trash = {
+        let history = history.clone();
+        let trash = trash.clone();
+
+        Callback::from(move |index: usize| {
+            if let Some(item) = trash.cards.get(index).cloned() {
+                purge_trash(&trash, &history, |trash| {
+                    trash.cards.retain(|existing| *existing != item);
+                });
+            }
+        })
+    };
+
+    let empty_trash = {
+        let history = history.clone();
+        let trash = trash.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            if trash.datasets.is_empty() && trash.cards.is_empty() {
+                return;
+            }
+            purge_trash(&trash, &history, |trash| {
+                trash.datasets.clear();
+                trash.cards.clear();
+            });
+        })
+    };
+
+    // Items past the new retention period are purged when the trash is saved.
+    let change_trash_retention = {
+        let trash = trash.clone();
+
+        Callback::from(move |days: u32| {
+            let mut updated_trash = (*trash).clone();
+            updated_trash.retention_days = days;
+            trash.set(updated_trash);
+        })
+    };
+
+    let open_snapshots = {
+        let snapshots = snapshots.clone();
+        let show_snapshots = show_snapshots.clone();
+        Callback::from(move |_: MouseEvent| {
+            let snapshots = snapshots.clone();
+            let show_snapshots = show_snapshots.clone();
+            spawn_local(async move {
+                snapshots.set(load_snapshots().await);
+                show_snapshots.set(true);
+            });
+        })
+    };
+
+    let close_snapshots = {
+        let show_snapshots = show_snapshots.clone();
+        Callback::from(move |_: MouseEvent| show_snapshots.set(false))
+    };
+
+    let take_snapshot_now = {
+        let snapshots = snapshots.clone();
+        let take_snapshot = take_snapshot.clone();
+        let notice = notice.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let snapshots = snapshots.clone();
+            let notice = notice.clone();
+            let datasets = Rc::new(take_snapshot().datasets);
+            spawn_local(async move {
+                let mut stored = load_snapshots().await;
+                push_snapshot(
+                    &mut stored,
+                    SnapshotKind::Manual,
+                    datasets,
+                    js_sys::Date::now(),
+                );
+                if let Err(error) = save_snapshots(&stored).await {
+                    notice.set(Some(error));
+                }
+                snapshots.set(stored);
+            });
+        })
+    };
+
+    let roll_back_everything = {
+        let record_history = record_history.clone();
+        let snapshots = snapshots.clone();
+        let replace_datasets = replace_datasets.clone();
+        let show_snapshots = show_snapshots.clone();
+
+        Callback::from(move |index: usize| {
+            if let Some(snapshot) = snapshots.get(index) {
+                record_history.emit("Roll back to snapshot");
+                replace_datasets.emit(snapshot.datasets.to_vec());
+                show_snapshots.set(false);
+            }
+        })
+    };
+
+    let roll_back_dataset = {
+        let record_history = record_history.clone();
+        let snapshots = snapshots.clone();
+        let replace_datasets = replace_datasets.clone();
+        let take_snapshot = take_snapshot.clone();
+        let show_snapshots = show_snapshots.clone();
+
+        Callback::from(move |(index, name): (usize, String)| {
+            let Some(restored) = snapshots.get(index).and_then(|snapshot| {
+                snapshot
+                    .datasets
+                    .iter()
+                    .find(|dataset| dataset.name == name)
+            }) else {
+                return;
+            };
+            record_history.emit("Roll back wordset to snapshot");
+            let mut datasets = take_snapshot().datasets;
+            match datasets.iter_mut().find(|dataset| dataset.name == name) {
+                Some(dataset) => *dataset = restored.clone(),
+                None => datasets.push(restored.clone()),
+            }
+            replace_datasets.emit(datasets);
+            show_snapshots.set(false);
+        })
+    };
+
     let update_information = {
         let flashcards = flashcards.clone();
         let known_cards = known_cards.clone();
+        let current_dataset = current_dataset.clone();
+        let datasets_list = datasets_list.clone();
+        let export_options = export_options.clone();
+        let show_export_dialog = show_export_dialog.clone();
+        let notice = notice.clone();
 
         Callback::from(move |_| {
-            if let Ok(bytes) = export_flashcards_csv(flashcards.iter().chain(known_cards.iter())) {
-                let _ = trigger_csv_download(&bytes, "updated_flashcards.csv");
+            let dataset =
+                active_dataset(&current_dataset, &flashcards, &known_cards, &datasets_list);
+            let exported_at = String::from(js_sys::Date::new_0().to_iso_string());
+            match export_wordset(&export_options, &dataset, &exported_at) {
+                Ok((bytes, file_name)) => {
+                    let _ = trigger_csv_download(&bytes, &file_name);
+                    show_export_dialog.set(false);
+                }
+                Err(error) => notice.set(Some(format!("Export failed: {error}"))),
+            }
+        })
+    };
+
+    let open_export = {
+        let current_dataset = current_dataset.clone();
+        let export_options = export_options.clone();
+        let show_export_dialog = show_export_dialog.clone();
+        let export_share_link = export_share_link.clone();
+        Callback::from(move |_: MouseEvent| {
+            export_share_link.set(None);
+            let today = String::from(js_sys::Date::new_0().to_iso_string());
+            export_options.set(ExportOptions {
+                scope: ExportScope::All,
+                file_stem: default_file_stem(&current_dataset, today.get(..10).unwrap_or_default()),
+                ..(*export_options).clone()
+            });
+            show_export_dialog.set(true);
+        })
+    };
+
+    let close_export = {
+        let show_export_dialog = show_export_dialog.clone();
+        Callback::from(move |_: MouseEvent| show_export_dialog.set(false))
+    };
+
+    let create_share_link = {
+        let flashcards = flashcards.clone();
+        let known_cards = known_cards.clone();
+        let current_dataset = current_dataset.clone();
+        let datasets_list = datasets_list.clone();
+        let export_options = export_options.clone();
+        let export_share_link = export_share_link.clone();
+        let notice = notice.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let dataset =
+                active_dataset(&current_dataset, &flashcards, &known_cards, &datasets_list);
+            match share_link(
+                &current_page_url(),
+                &scoped_dataset(&dataset, &export_options.scope),
+            ) {
+                Ok(link) => export_share_link.set(Some(link)),
+                Err(error) => notice.set(Some(format!("Sharing failed: {error}"))),
+            }
+        })
+    };
+
+    let copy_share_link = {
+        let export_share_link = export_share_link.clone();
+        let notice = notice.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let (Some(link), Some(window)) = ((*export_share_link).clone(), web_sys::window())
+            else {
+                return;
+            };
+            let copied = window.navigator().clipboard().write_text(&link);
+            let notice = notice.clone();
+            spawn_local(async move {
+                let message = match JsFuture::from(copied).await {
+                    Ok(_) => "The share link was copied to the clipboard.",
+                    Err(_) => "The share link could not be copied. Select it and copy it by hand.",
+                };
+                notice.set(Some(message.to_string()));
+            });
+        })
+    };
+
+    let import_shared_wordset = {
+        let shared_wordset = shared_wordset.clone();
+        let datasets_list = datasets_list.clone();
+        let record_history = record_history.clone();
+        let notice = notice.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let Some(mut dataset) = (*shared_wordset).clone() else {
+                return;
+            };
+            record_history.emit("Import shared wordset");
+            let mut datasets = (*datasets_list).clone();
+            dataset.name = unique_dataset_name(&dataset.name, "shared", &datasets);
+            notice.set(Some(format!(
+                "Added wordset \"{}\" with {} cards.",
+                dataset.name,
+                dataset.flashcards.len() + dataset.known_cards.len()
+            )));
+            datasets.push(dataset);
+            datasets_list.set(datasets);
+            shared_wordset.set(None);
+        })
+    };
+
+    let dismiss_shared_wordset = {
+        let shared_wordset = shared_wordset.clone();
+        Callback::from(move |_: MouseEvent| shared_wordset.set(None))
+    };
+
+    let open_qr = {
+        let qr_scope = qr_scope.clone();
+        let show_qr_dialog = show_qr_dialog.clone();
+        Callback::from(move |_: MouseEvent| {
+            qr_scope.set(ExportScope::All);
+            show_qr_dialog.set(true);
+        })
+    };
+
+    let change_qr_scope = {
+        let qr_scope = qr_scope.clone();
+        Callback::from(move |scope: ExportScope| qr_scope.set(scope))
+    };
+
+    let close_qr = {
+        let show_qr_dialog = show_qr_dialog.clone();
+        Callback::from(move |_: MouseEvent| show_qr_dialog.set(false))
+    };
+
+    let open_scanner = {
+        let show_qr_scanner = show_qr_scanner.clone();
+        Callback::from(move |_: MouseEvent| show_qr_scanner.set(true))
+    };
+
+    let close_scanner = {
+        let show_qr_scanner = show_qr_scanner.clone();
+        Callback::from(move |_: MouseEvent| show_qr_scanner.set(false))
+    };
+
+    // Scanned wordsets go through the same preview as share links before import.
+    let receive_scanned_wordset = {
+        let shared_wordset = shared_wordset.clone();
+        let show_qr_scanner = show_qr_scanner.clone();
+        Callback::from(move |dataset: Dataset| {
+            show_qr_scanner.set(false);
+            shared_wordset.set(Some(dataset));
+        })
+    };
+
+    let change_print_options = {
+        let print_options = print_options.clone();
+        Callback::from(move |options: PrintOptions| print_options.set(options))
+    };
+
+    let open_print = {
+        let print_options = print_options.clone();
+        let show_print_dialog = show_print_dialog.clone();
+        Callback::from(move |_: MouseEvent| {
+            print_options.set(PrintOptions {
+                scope: ExportScope::All,
+                ..(*print_options).clone()
+            });
+            show_print_dialog.set(true);
+        })
+    };
+
+    let close_print = {
+        let show_print_dialog = show_print_dialog.clone();
+        Callback::from(move |_: MouseEvent| show_print_dialog.set(false))
+    };
+
+    let print_cards = {
+        let flashcards = flashcards.clone();
+        let known_cards = known_cards.clone();
+        let current_dataset = current_dataset.clone();
+        let datasets_list = datasets_list.clone();
+        let print_options = print_options.clone();
+        let show_print_dialog = show_print_dialog.clone();
+        let notice = notice.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let dataset =
+                active_dataset(&current_dataset, &flashcards, &known_cards, &datasets_list);
+            let html = print_sheet_html(&dataset, &print_options);
+            match open_print_view(&html, &format!("{} cards.html", dataset.name)) {
+                Ok(()) => show_print_dialog.set(false),
+                Err(_) => notice.set(Some("The print view could not be opened.".to_string())),
             }
         })
     };
 
     let show_export = !current_dataset.is_empty();
-    let show_import = show_export && flashcards.is_empty() && known_cards.is_empty();
+    let show_import = show_export;
 
     let known_total = flashcards.len() + known_cards.len();
 
+    let suggestions = dictionary
+        .as_ref()
+        .map(|dictionary| dictionary.lookup(&new_word).to_vec())
+        .unwrap_or_default();
+
     let position_counter = if !flashcards.is_empty() {
         html! {
             <p class="position-counter">
@@ -672,12 +2007,102 @@ pub fn app() -> Html {
                 <button class="btn btn-secondary btn-small help-trigger-btn" onclick={open_help}>
                     {"? Help & About"}
                 </button>
+                <button class="btn btn-secondary btn-small help-trigger-btn" onclick={open_snapshots}>
+                    {"⟲ Snapshots"}
+                </button>
+                <button class="btn btn-secondary btn-small help-trigger-btn" onclick={open_trash}>
+                    { format!("🗑 Trash ({})", trash.datasets.len() + trash.cards.len()) }
+                </button>
             </header>
 
+            if let Some(message) = (*notice).clone() {
+                <div class="panel app-notice">
+                    <p class="app-notice-text">{ message }</p>
+                    <button
+                        class="btn btn-secondary btn-small"
+                        onclick={{
+                            let notice = notice.clone();
+                            Callback::from(move |_: MouseEvent| notice.set(None))
+                        }}
+                    >
+                        {"Dismiss"}
+                    </button>
+                </div>
+            }
+
             if *show_help {
                 <HelpPanel on_close={close_help} />
             }
 
+            if *show_snapshots {
+                <SnapshotPanel
+                    snapshots={(*snapshots).clone()}
+                    on_close={close_snapshots}
+                    on_take_snapshot={take_snapshot_now}
+                    on_restore_all={roll_back_everything}
+                    on_restore_dataset={roll_back_dataset}
+                />
+            }
+
+            if *show_export_dialog {
+                <ExportDialog
+                    dataset={active_dataset(&current_dataset, &flashcards, &known_cards, &datasets_list)}
+                    options={(*export_options).clone()}
+                    on_change={change_export_options}
+                    on_export={update_information.clone()}
+                    on_close={close_export}
+                    share_link={(*export_share_link).clone()}
+                    on_share={create_share_link}
+                    on_copy_link={copy_share_link}
+                />
+            }
+
+            if *show_qr_dialog {
+                <QrDialog
+                    dataset={active_dataset(&current_dataset, &flashcards, &known_cards, &datasets_list)}
+                    scope={(*qr_scope).clone()}
+                    on_scope_change={change_qr_scope}
+                    on_close={close_qr}
+                />
+            }
+
+            if *show_qr_scanner {
+                <QrScanner on_scanned={receive_scanned_wordset} on_close={close_scanner} />
+            }
+
+            if let Some(dataset) = (*shared_wordset).clone() {
+                <SharedWordsetPreview
+                    {dataset}
+                    on_import={import_shared_wordset}
+                    on_dismiss={dismiss_shared_wordset}
+                />
+            }
+
+            if *show_print_dialog {
+                <PrintDialog
+                    dataset={active_dataset(&current_dataset, &flashcards, &known_cards, &datasets_list)}
+                    options={(*print_options).clone()}
+                    on_change={change_print_options}
+                    on_print={print_cards}
+                    on_close={close_print}
+                />
+            }
+
+            if *show_trash {
+                <TrashPanel
+                    trash={(*trash).clone()}
+                    dataset_names={datasets_list.iter().map(|dataset| dataset.name.clone()).collect::<Vec<_>>()}
+                    current_dataset={(*current_dataset).clone()}
+                    on_close={close_trash}
+                    on_restore_dataset={restore_dataset_from_trash}
+                    on_purge_dataset={purge_dataset_from_trash}
+                    on_restore_card={restore_card_from_trash}
+                    on_purge_card={purge_card_from_trash}
+                    on_empty={empty_trash}
+                    on_retention_change={change_trash_retention}
+                />
+            }
+
             <DatasetPanel
                 datasets={(*datasets_list).clone()}
                 current_dataset={(*current_dataset).clone()}
@@ -694,7 +2119,27 @@ pub fn app() -> Html {
                 show_import={show_import}
                 show_export={show_export}
                 on_file_select={on_file_select.clone()}
-                on_download={update_information.clone()}
+                pending_import={(*pending_import).clone()}
+                on_import_column_change={change_import_column}
+                on_import_header_change={change_import_header}
+                on_import_strategy_change={change_import_strategy}
+                on_import_dialect_change={change_import_dialect}
+                on_import_encoding_change={change_import_encoding}
+                on_import_split_change={change_import_split}
+                on_import_quizlet_change={change_import_quizlet}
+                on_import_pleco_change={change_import_pleco}
+                on_import_target_change={change_import_target}
+                on_import_sheet_change={change_import_sheet}
+                queued_imports={import_queue.len()}
+                on_files_drop={import_files}
+                on_paste_import={import_pasted_text}
+                current_cards={[(*flashcards).clone(), (*known_cards).clone()].concat()}
+                on_import_confirm={confirm_import}
+                on_import_cancel={cancel_import}
+                on_open_export={open_export}
+                on_open_print={open_print}
+                on_open_qr={open_qr}
+                on_open_scanner={open_scanner}
                 renaming_dataset={(*renaming_dataset).clone()}
                 rename_input={(*rename_input).clone()}
                 on_start_rename={on_start_rename.clone()}
@@ -708,6 +2153,10 @@ pub fn app() -> Html {
                 on_toggle_direction={toggle_direction.clone()}
                 on_randomize={randomize_cards.clone()}
                 on_open_add={open_add.clone()}
+                undo_label={history.undo_label().map(str::to_owned)}
+                redo_label={history.redo_label().map(str::to_owned)}
+                on_undo={undo.reform(|_: MouseEvent| ())}
+                on_redo={redo.reform(|_: MouseEvent| ())}
             />
 
             <AddFlashcardForm
@@ -720,6 +2169,12 @@ pub fn app() -> Html {
                 on_translation_input={oninput_new_translation.clone()}
                 on_save={save_new.clone()}
                 on_cancel={close_add.clone()}
+                suggestions={suggestions}
+                on_pick_reading={pick_reading}
+                dictionary_entries={dictionary.as_ref().map(|dictionary| dictionary.entry_count())}
+                dictionary_loading={*dictionary_loading}
+                on_dictionary_select={on_dictionary_select}
+                on_dictionary_remove={remove_dictionary}
             />
 
             <section class="unknown-panel panel">
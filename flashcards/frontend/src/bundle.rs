@@ -0,0 +1,122 @@
+//! Wordset bundles: a zip archive moving a complete wordset in one file.
+//!
+//! ```text
+//! wordset.json   the wordset JSON file
+//! media/         images and audio the cards refer to
+//! ```
+//!
+//! Cards cannot hold images or audio yet, so bundles are written without a `media/`
+//! folder and any files in it are left out on import. The folder is reserved so that
+//! bundles made once cards carry media keep the same layout.
+
+use std::io::{Cursor, Read, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::json_io::{export_wordset_json, read_wordset_json};
+use crate::model::Dataset;
+
+/// Name of the wordset file inside a bundle.
+pub const WORDSET_ENTRY: &str = "wordset.json";
+/// Largest wordset file a bundle may unpack to.
+const MAX_WORDSET_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Packs `dataset` into a bundle. `exported_at` is recorded in the wordset file.
+pub fn export_bundle(dataset: &Dataset, exported_at: &str) -> Result<Vec<u8>, String> {
+    let json = export_wordset_json(dataset, exported_at).map_err(|error| error.to_string())?;
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    writer
+        .start_file(WORDSET_ENTRY, options)
+        .map_err(|error| error.to_string())?;
+    writer.write_all(&json).map_err(|error| error.to_string())?;
+    Ok(writer
+        .finish()
+        .map_err(|error| error.to_string())?
+        .into_inner())
+}
+
+/// Unpacks the wordset of a bundle, checking it like a wordset JSON file.
+pub fn read_bundle(bytes: &[u8]) -> Result<Dataset, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|_| "This file is not a zip archive.".to_string())?;
+    let entry = archive.by_name(WORDSET_ENTRY).map_err(|_| {
+        format!("This zip file has no {WORDSET_ENTRY}, so it is not a wordset bundle.")
+    })?;
+    let mut json = Vec::new();
+    entry
+        .take(MAX_WORDSET_BYTES + 1)
+        .read_to_end(&mut json)
+        .map_err(|error| format!("The wordset bundle is damaged ({error})."))?;
+    if json.len() as u64 > MAX_WORDSET_BYTES {
+        return Err("The wordset in this bundle is too large to open.".to_string());
+    }
+    let text = String::from_utf8(json)
+        .map_err(|_| format!("The {WORDSET_ENTRY} of this bundle is not UTF-8 encoded."))?;
+    read_wordset_json(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    use super::{export_bundle, read_bundle};
+    use crate::json_io::export_wordset_json;
+    use crate::model::{Dataset, Flashcard};
+
+    fn dataset() -> Dataset {
+        Dataset {
+            name: "HSK 1".to_string(),
+            flashcards: vec![Flashcard {
+                word: "你好".to_string(),
+                pinyin: Some("nǐ hǎo".to_string()),
+                translation: "hello".to_string(),
+                known: false,
+                tags: vec!["greeting".to_string()],
+            }],
+            known_cards: Vec::new(),
+            column_mapping: None,
+        }
+    }
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn round_trips_the_wordset() {
+        let bundle = export_bundle(&dataset(), "2026-10-18T09:30:00.000Z").unwrap();
+        assert!(bundle.starts_with(b"PK"));
+        assert!(read_bundle(&bundle).unwrap() == dataset());
+    }
+
+    #[test]
+    fn leaves_out_media_files() {
+        let json = export_wordset_json(&dataset(), "").unwrap();
+        let bundle = zip(&[
+            ("media/hello.mp3", b"ID3"),
+            ("wordset.json", &json),
+            ("media/hello.png", b"\x89PNG"),
+        ]);
+        assert!(read_bundle(&bundle).unwrap() == dataset());
+    }
+
+    #[test]
+    fn explains_what_is_wrong_with_other_files() {
+        let error = |bytes: &[u8]| read_bundle(bytes).err().unwrap_or_default();
+        assert!(error(b"word,translation\n").contains("not a zip archive"));
+        assert!(error(&zip(&[("collection.anki2", b"")])).contains("no wordset.json"));
+        assert!(error(&zip(&[("wordset.json", b"[]")])).contains("not a wordset"));
+    }
+}
@@ -0,0 +1,173 @@
+//! CC-CEDICT dictionary files, used to suggest pinyin and translations for new cards.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use crate::pinyin::numbered_to_marked;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Reading {
+    pub pinyin: String,
+    pub translation: String,
+}
+
+/// Readings keyed by both the simplified and the traditional form of each word.
+#[derive(Default)]
+pub struct Dictionary {
+    entries: HashMap<String, Vec<Reading>>,
+    entry_count: usize,
+}
+
+impl Dictionary {
+    pub fn entry_count(&self) -> usize {
+        self.entry_count
+    }
+
+    pub fn lookup(&self, word: &str) -> &[Reading] {
+        self.entries
+            .get(word.trim())
+            .map_or(&[], |readings| readings.as_slice())
+    }
+
+    fn insert(&mut self, word: &str, reading: &Reading) {
+        let readings = self.entries.entry(word.to_string()).or_default();
+        if !readings.contains(reading) {
+            readings.push(reading.clone());
+        }
+    }
+}
+
+/// Parses a line such as `中國 中国 [Zhong1 guo2] /China/` into its traditional
+/// and simplified headwords and reading.
+fn parse_line(line: &str) -> Option<(&str, &str, Reading)> {
+    let (headwords, rest) = line.split_once(" [")?;
+    let (traditional, simplified) = headwords.trim().split_once(' ')?;
+    let (pinyin, definitions) = rest.split_once("] /")?;
+    let definitions: Vec<&str> = definitions
+        .trim_end()
+        .split('/')
+        .map(str::trim)
+        .filter(|definition| !definition.is_empty())
+        .collect();
+    if definitions.is_empty() {
+        return None;
+    }
+    Some((
+        traditional,
+        simplified,
+        Reading {
+            pinyin: numbered_to_marked(pinyin.trim()),
+            translation: definitions.join("; "),
+        },
+    ))
+}
+
+/// Reads a CC-CEDICT text file, skipping `#` comment lines.
+pub fn parse_cedict(text: &str) -> Result<Dictionary, String> {
+    let mut dictionary = Dictionary::default();
+    for line in text.lines() {
+        let line = line.trim_start_matches('\u{feff}');
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let Some((traditional, simplified, reading)) = parse_line(line) else {
+            continue;
+        };
+        dictionary.insert(simplified, &reading);
+        if traditional != simplified {
+            dictionary.insert(traditional, &reading);
+        }
+        dictionary.entry_count += 1;
+    }
+    if dictionary.entry_count == 0 {
+        return Err("No CC-CEDICT entries were found in this file.".to_string());
+    }
+    Ok(dictionary)
+}
+
+/// Text of a downloaded CC-CEDICT file, unpacking it first when it is the zip
+/// archive offered on the CC-CEDICT download page.
+pub fn cedict_text(file_name: &str, bytes: &[u8]) -> Result<String, String> {
+    if !file_name.to_lowercase().ends_with(".zip") {
+        return Ok(String::from_utf8_lossy(bytes).into_owned());
+    }
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|_| "This file is not a valid zip archive.".to_string())?;
+    let name = archive
+        .file_names()
+        .find(|name| {
+            let name = name.to_lowercase();
+            name.ends_with(".u8") || name.ends_with(".txt")
+        })
+        .map(str::to_string)
+        .ok_or_else(|| "The archive does not contain a CC-CEDICT text file.".to_string())?;
+    let mut text = String::new();
+    archive
+        .by_name(&name)
+        .map_err(|error| error.to_string())?
+        .read_to_string(&mut text)
+        .map_err(|_| "The dictionary in the archive is not valid UTF-8.".to_string())?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cedict_text, parse_cedict, Reading};
+    use std::io::Write;
+
+    const SAMPLE: &str = "# CC-CEDICT\n\
+        #! version=1\n\
+        中國 中国 [Zhong1 guo2] /China/\n\
+        行 行 [hang2] /row/line/profession/\n\
+        行 行 [xing2] /to walk/to go/capable/\n\
+        綠 绿 [lu:4] /green/\n\
+        broken line without brackets\n";
+
+    #[test]
+    fn looks_up_simplified_and_traditional_forms() {
+        let dictionary = parse_cedict(SAMPLE).unwrap();
+
+        assert_eq!(dictionary.entry_count(), 4);
+        let china = Reading {
+            pinyin: "Zhōng guó".to_string(),
+            translation: "China".to_string(),
+        };
+        assert_eq!(dictionary.lookup("中国"), dictionary.lookup("中國"));
+        assert_eq!(dictionary.lookup("中国"), [china]);
+        assert_eq!(dictionary.lookup("绿")[0].pinyin, "lǜ");
+        assert!(dictionary.lookup("你好").is_empty());
+    }
+
+    #[test]
+    fn keeps_every_reading_in_order() {
+        let dictionary = parse_cedict(SAMPLE).unwrap();
+        let readings = dictionary.lookup(" 行 ");
+
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[0].pinyin, "háng");
+        assert_eq!(readings[0].translation, "row; line; profession");
+        assert_eq!(readings[1].pinyin, "xíng");
+        assert!(parse_cedict("# only comments\n").is_err());
+    }
+
+    #[test]
+    fn unpacks_zipped_downloads() {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer
+            .start_file(
+                "cedict_ts.u8",
+                zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored),
+            )
+            .unwrap();
+        writer.write_all(SAMPLE.as_bytes()).unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+
+        assert_eq!(
+            cedict_text("cedict_1_0_ts_utf-8_mdbg.zip", &archive).unwrap(),
+            SAMPLE
+        );
+        assert_eq!(cedict_text("cedict.u8", SAMPLE.as_bytes()).unwrap(), SAMPLE);
+        assert!(cedict_text("cedict.zip", b"not a zip").is_err());
+    }
+}
@@ -1,6 +1,8 @@
-use web_sys::{InputEvent, MouseEvent};
+use web_sys::{Event, InputEvent, MouseEvent};
 use yew::prelude::*;
 
+use crate::cedict::Reading;
+
 #[derive(Properties, PartialEq)]
 pub struct AddFlashcardFormProps {
     pub visible: bool,
@@ -12,6 +14,14 @@ pub struct AddFlashcardFormProps {
     pub on_translation_input: Callback<InputEvent>,
     pub on_save: Callback<MouseEvent>,
     pub on_cancel: Callback<MouseEvent>,
+    /// Dictionary readings of the word typed so far.
+    pub suggestions: Vec<Reading>,
+    pub on_pick_reading: Callback<Reading>,
+    /// Number of entries in the loaded CC-CEDICT dictionary, if one is loaded.
+    pub dictionary_entries: Option<usize>,
+    pub dictionary_loading: bool,
+    pub on_dictionary_select: Callback<Event>,
+    pub on_dictionary_remove: Callback<MouseEvent>,
 }
 
 #[function_component(AddFlashcardForm)]
@@ -20,12 +30,51 @@ pub fn add_flashcard_form(props: &AddFlashcardFormProps) -> Html {
         return html! {};
     }
 
+    let dictionary_status = if props.dictionary_loading {
+        html! { <span class="muted-note">{"Loading dictionary…"}</span> }
+    } else if let Some(entries) = props.dictionary_entries {
+        html! {
+            <>
+                <span class="muted-note">{format!("CC-CEDICT loaded ({entries} entries)")}</span>
+                <button class="btn btn-muted" onclick={props.on_dictionary_remove.clone()}>{"Remove"}</button>
+            </>
+        }
+    } else {
+        html! {
+            <span class="muted-note">
+                {"Load a CC-CEDICT file to get pinyin and translation suggestions."}
+            </span>
+        }
+    };
+
     html! {
         <div class="panel add-form-panel">
             <h3 class="panel-title">{"Add New Flashcard"}</h3>
             <div class="field-row">
                 <input class="text-input" placeholder="Chinese character" value={props.new_word.clone()} oninput={props.on_word_input.clone()} />
             </div>
+            if !props.suggestions.is_empty() {
+                <div class="dictionary-suggestions">
+                    { for props.suggestions.iter().map(|reading| {
+                        let selected = reading.pinyin == props.new_pinyin
+                            && reading.translation == props.new_translation;
+                        let onclick = {
+                            let reading = reading.clone();
+                            props.on_pick_reading.reform(move |_: MouseEvent| reading.clone())
+                        };
+                        html! {
+                            <button
+                                class={classes!("dictionary-reading", selected.then_some("selected"))}
+                                {onclick}
+                            >
+                                <strong>{&reading.pinyin}</strong>
+                                {" "}
+                                {&reading.translation}
+                            </button>
+                        }
+                    }) }
+                </div>
+            }
             <div class="field-row">
                 <input class="text-input" placeholder="Pinyin" value={props.new_pinyin.clone()} oninput={props.on_pinyin_input.clone()} />
             </div>
@@ -36,6 +85,18 @@ pub fn add_flashcard_form(props: &AddFlashcardFormProps) -> Html {
                 <button class="btn btn-primary" onclick={props.on_save.clone()}>{"Save"}</button>
                 <button class="btn btn-muted" onclick={props.on_cancel.clone()}>{"Cancel"}</button>
             </div>
+            <div class="dictionary-row">
+                { dictionary_status }
+                <label class="btn btn-secondary file-button">
+                    {"Load CC-CEDICT"}
+                    <input
+                        type="file"
+                        class="file-button-input"
+                        accept=".u8,.txt,.zip"
+                        onchange={props.on_dictionary_select.clone()}
+                    />
+                </label>
+            </div>
         </div>
     }
 }
@@ -1,7 +1,12 @@
-use web_sys::{Event, InputEvent, MouseEvent};
+use encoding_rs::Encoding;
+use web_sys::{DragEvent, Event, HtmlTextAreaElement, InputEvent, MouseEvent};
 use yew::prelude::*;
 
-use crate::model::Dataset;
+use crate::components::import_preview::ImportPreview;
+use crate::csv_io::CsvDialect;
+use crate::import::{ImportTarget, MergeStrategy, PendingImport};
+use crate::model::{ColumnField, Dataset, Flashcard};
+use crate::quizlet::QuizletSeparators;
 
 #[derive(Properties, PartialEq)]
 pub struct DatasetPanelProps {
@@ -17,7 +22,33 @@ pub struct DatasetPanelProps {
     pub show_import: bool,
     pub show_export: bool,
     pub on_file_select: Callback<Event>,
-    pub on_download: Callback<MouseEvent>,
+    pub pending_import: Option<PendingImport>,
+    pub on_import_column_change: Callback<(usize, ColumnField)>,
+    pub on_import_header_change: Callback<bool>,
+    pub on_import_strategy_change: Callback<MergeStrategy>,
+    pub on_import_dialect_change: Callback<CsvDialect>,
+    pub on_import_encoding_change: Callback<&'static Encoding>,
+    pub on_import_split_change: Callback<bool>,
+    pub on_import_quizlet_change: Callback<Option<QuizletSeparators>>,
+    pub on_import_pleco_change: Callback<bool>,
+    pub on_import_target_change: Callback<ImportTarget>,
+    pub on_import_sheet_change: Callback<usize>,
+    /// Files waiting to be previewed after the current import.
+    pub queued_imports: usize,
+    pub on_files_drop: Callback<Vec<web_sys::File>>,
+    pub on_paste_import: Callback<String>,
+    /// Cards of the selected wordset, unknown ones first.
+    pub current_cards: Vec<Flashcard>,
+    pub on_import_confirm: Callback<MouseEvent>,
+    pub on_import_cancel: Callback<MouseEvent>,
+    /// Opens the export dialog for the active wordset.
+    pub on_open_export: Callback<MouseEvent>,
+    /// Opens the print dialog for the active wordset.
+    pub on_open_print: Callback<MouseEvent>,
+    /// Shows the active wordset as QR codes for another device.
+    pub on_open_qr: Callback<MouseEvent>,
+    /// Opens the camera to read a wordset from QR codes.
+    pub on_open_scanner: Callback<MouseEvent>,
     pub renaming_dataset: Option<String>,
     pub rename_input: String,
     pub on_start_rename: Callback<String>,
@@ -28,6 +59,64 @@ pub struct DatasetPanelProps {
 
 #[function_component(DatasetPanel)]
 pub fn dataset_panel(props: &DatasetPanelProps) -> Html {
+    let dragging = use_state(|| false);
+    let show_paste = use_state(|| false);
+    let paste_text = use_state(String::new);
+
+    let ondragover = {
+        let dragging = dragging.clone();
+        Callback::from(move |event: DragEvent| {
+            event.prevent_default();
+            dragging.set(true);
+        })
+    };
+
+    let ondragleave = {
+        let dragging = dragging.clone();
+        Callback::from(move |_: DragEvent| dragging.set(false))
+    };
+
+    let ondrop = {
+        let dragging = dragging.clone();
+        let on_files_drop = props.on_files_drop.clone();
+        Callback::from(move |event: DragEvent| {
+            event.prevent_default();
+            dragging.set(false);
+            if let Some(files) = event.data_transfer().and_then(|transfer| transfer.files()) {
+                on_files_drop.emit(
+                    (0..files.length())
+                        .filter_map(|index| files.get(index))
+                        .collect(),
+                );
+            }
+        })
+    };
+
+    let toggle_paste = {
+        let show_paste = show_paste.clone();
+        Callback::from(move |_: MouseEvent| show_paste.set(!*show_paste))
+    };
+
+    let on_paste_input = {
+        let paste_text = paste_text.clone();
+        Callback::from(move |event: InputEvent| {
+            if let Some(area) = event.target_dyn_into::<HtmlTextAreaElement>() {
+                paste_text.set(area.value());
+            }
+        })
+    };
+
+    let preview_paste = {
+        let show_paste = show_paste.clone();
+        let paste_text = paste_text.clone();
+        let on_paste_import = props.on_paste_import.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_paste_import.emit((*paste_text).clone());
+            paste_text.set(String::new());
+            show_paste.set(false);
+        })
+    };
+
     let dataset_list = if props.datasets.is_empty() {
         html! { <p class="muted-note">{"No wordsets yet. Create one below."}</p> }
     } else {
@@ -108,7 +197,12 @@ pub fn dataset_panel(props: &DatasetPanelProps) -> Html {
     };
 
     html! {
-        <section class="panel">
+        <section
+            class={classes!("panel", dragging.then_some("drop-active"))}
+            {ondragover}
+            {ondragleave}
+            {ondrop}
+        >
             <h3 class="panel-title">{"Wordsets"}</h3>
             <div class="panel-content">
                 { dataset_list }
@@ -117,7 +211,40 @@ pub fn dataset_panel(props: &DatasetPanelProps) -> Html {
                 <button class="btn btn-secondary" onclick={props.on_toggle_input.clone()}>
                     { if props.show_dataset_input { "Cancel" } else { "New Wordset" } }
                 </button>
+                <label class="btn btn-secondary file-button" title="Create a wordset from an Anki .apkg deck">
+                    {"Import Anki Deck"}
+                    <input
+                        class="file-button-input"
+                        type="file"
+                        accept=".apkg"
+                        onchange={props.on_file_select.clone()}
+                    />
+                </label>
+                <button class="btn btn-secondary" onclick={toggle_paste}>
+                    { if *show_paste { "Cancel Paste" } else { "Paste Rows" } }
+                </button>
+                <button class="btn btn-secondary" onclick={props.on_open_scanner.clone()}>
+                    {"Scan QR Codes…"}
+                </button>
             </div>
+            if *show_paste {
+                <div class="paste-import">
+                    <textarea
+                        class="text-input paste-import-text"
+                        rows="6"
+                        placeholder="Paste rows copied from a spreadsheet or text file, one card per line"
+                        value={(*paste_text).clone()}
+                        oninput={on_paste_input}
+                    />
+                    <button
+                        class="btn btn-primary"
+                        disabled={paste_text.trim().is_empty()}
+                        onclick={preview_paste}
+                    >
+                        {"Preview Import"}
+                    </button>
+                </div>
+            }
             { if props.show_dataset_input {
                 html! {
                     <div class="inline-create-row">
@@ -137,15 +264,42 @@ pub fn dataset_panel(props: &DatasetPanelProps) -> Html {
             { if props.show_export {
                 html! {
                     <div class="panel-actions">
-                        <button class="btn btn-secondary" onclick={props.on_download.clone()}>
-                            {"Export Flashcards"}
+                        <button class="btn btn-secondary" onclick={props.on_open_export.clone()}>
+                            {"Export Flashcards…"}
+                        </button>
+                        <button class="btn btn-secondary" onclick={props.on_open_print.clone()}>
+                            {"Print Cards…"}
+                        </button>
+                        <button class="btn btn-secondary" onclick={props.on_open_qr.clone()}>
+                            {"Show QR Codes…"}
                         </button>
                     </div>
                 }
             } else {
                 html! {}
             } }
-            { if props.show_import {
+            { if let Some(pending) = props.pending_import.clone() {
+                html! {
+                    <ImportPreview
+                        {pending}
+                        on_column_change={props.on_import_column_change.clone()}
+                        on_header_change={props.on_import_header_change.clone()}
+                        on_strategy_change={props.on_import_strategy_change.clone()}
+                        on_dialect_change={props.on_import_dialect_change.clone()}
+                        on_encoding_change={props.on_import_encoding_change.clone()}
+                        on_split_change={props.on_import_split_change.clone()}
+                        on_quizlet_change={props.on_import_quizlet_change.clone()}
+                        on_pleco_change={props.on_import_pleco_change.clone()}
+                        on_target_change={props.on_import_target_change.clone()}
+                        on_sheet_change={props.on_import_sheet_change.clone()}
+                        current_dataset={props.current_dataset.clone()}
+                        queued={props.queued_imports}
+                        existing={props.current_cards.clone()}
+                        on_confirm={props.on_import_confirm.clone()}
+                        on_cancel={props.on_import_cancel.clone()}
+                    />
+                }
+            } else if props.show_import {
                 html! {
                     <div class="import-group">
                         <label class="input-label" for="import-flashcards-input">{"Import Flashcards"}</label>
@@ -153,9 +307,11 @@ pub fn dataset_panel(props: &DatasetPanelProps) -> Html {
                             id="import-flashcards-input"
                             class="file-input"
                             type="file"
-                            accept=".csv"
+                            accept=".csv,.tsv,.txt,.md,.markdown,.xlsx,.apkg,.json,.zip"
+                            multiple=true
                             onchange={props.on_file_select.clone()}
                         />
+                        <p class="muted-note">{"…or drop one or more files onto this panel."}</p>
                     </div>
                 }
             } else {
@@ -0,0 +1,370 @@
+use std::collections::BTreeSet;
+
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, InputEvent, MouseEvent};
+use yew::prelude::*;
+
+use crate::components::import_preview::SeparatorChoice;
+use crate::export::{
+    dataset_tags, export_file_name, export_format_label, scoped_dataset, ExportFormat,
+    ExportOptions, ExportScope, EXPORT_FIELDS, EXPORT_FORMATS,
+};
+use crate::import::field_label;
+use crate::model::Dataset;
+use crate::quizlet::{ROW_SEPARATORS, TERM_SEPARATORS};
+use crate::share::LONG_LINK_LENGTH;
+
+#[derive(Properties, PartialEq)]
+pub struct ExportDialogProps {
+    /// The wordset being exported, with its current cards.
+    pub dataset: Dataset,
+    pub options: ExportOptions,
+    pub on_change: Callback<ExportOptions>,
+    pub on_export: Callback<MouseEvent>,
+    pub on_close: Callback<MouseEvent>,
+    /// Link to the chosen cards, once one has been created for the current options.
+    pub share_link: Option<String>,
+    pub on_share: Callback<MouseEvent>,
+    pub on_copy_link: Callback<MouseEvent>,
+}
+
+fn scope_value(scope: &ExportScope) -> String {
+    match scope {
+        ExportScope::All => "all".to_string(),
+        ExportScope::Unknown => "unknown".to_string(),
+        ExportScope::Known => "known".to_string(),
+        ExportScope::Selected(_) => "selected".to_string(),
+        ExportScope::Tag(tag) => format!("tag:{tag}"),
+    }
+}
+
+fn scope_from_value(value: &str) -> ExportScope {
+    match value {
+        "unknown" => ExportScope::Unknown,
+        "known" => ExportScope::Known,
+        "selected" => ExportScope::Selected(BTreeSet::new()),
+        value => value
+            .strip_prefix("tag:")
+            .map_or(ExportScope::All, |tag| ExportScope::Tag(tag.to_string())),
+    }
+}
+
+/// Checklist of every card, shown while the scope is "Selected cards".
+fn card_picker(
+    dataset: &Dataset,
+    selected: &BTreeSet<usize>,
+    on_change: &Callback<ExportScope>,
+) -> Html {
+    let set_selection = |selection: BTreeSet<usize>| {
+        on_change.reform(move |_: MouseEvent| ExportScope::Selected(selection.clone()))
+    };
+    let total = dataset.flashcards.len() + dataset.known_cards.len();
+
+    html! {
+        <div class="export-picker">
+            <div class="panel-actions">
+                <button class="btn btn-muted btn-small" onclick={set_selection((0..total).collect())}>
+                    {"Select All"}
+                </button>
+                <button class="btn btn-muted btn-small" onclick={set_selection(BTreeSet::new())}>
+                    {"Select None"}
+                </button>
+            </div>
+            <ul class="export-card-list">
+                { for dataset.flashcards.iter().chain(&dataset.known_cards).enumerate().map(|(index, card)| {
+                    let checked = selected.contains(&index);
+                    let mut toggled = selected.clone();
+                    if checked {
+                        toggled.remove(&index);
+                    } else {
+                        toggled.insert(index);
+                    }
+                    let onchange =
+                        on_change.reform(move |_: Event| ExportScope::Selected(toggled.clone()));
+                    html! {
+                        <li>
+                            <label class="export-option">
+                                <input type="checkbox" {checked} {onchange} />
+                                <strong>{ &card.word }</strong>
+                                <span class="muted-note">{ &card.translation }</span>
+                                if card.known {
+                                    <span class="status-badge-known">{"Known"}</span>
+                                }
+                            </label>
+                        </li>
+                    }
+                }) }
+            </ul>
+        </div>
+    }
+}
+
+/// Choice of the cards of `dataset` to include, by status, tag or one by one,
+/// with the number of cards chosen. `verb` completes "N cards will be …".
+pub fn scope_field(
+    dataset: &Dataset,
+    scope: &ExportScope,
+    verb: &str,
+    on_change: Callback<ExportScope>,
+) -> Html {
+    let tags = dataset_tags(dataset);
+    let scoped = scoped_dataset(dataset, scope);
+    let card_count = scoped.flashcards.len() + scoped.known_cards.len();
+    let on_select = on_change.reform(|event: Event| {
+        event
+            .target_dyn_into::<HtmlSelectElement>()
+            .map_or(ExportScope::All, |select| scope_from_value(&select.value()))
+    });
+
+    html! {
+        <>
+            <div class="export-field">
+                <label class="input-label">{"Cards"}</label>
+                <select class="text-input" onchange={on_select}>
+                    <option value="all" selected={*scope == ExportScope::All}>{"All cards"}</option>
+                    <option value="unknown" selected={*scope == ExportScope::Unknown}>{"Unknown cards only"}</option>
+                    <option value="known" selected={*scope == ExportScope::Known}>{"Known cards only"}</option>
+                    <option value="selected" selected={matches!(scope, ExportScope::Selected(_))}>{"Selected cards"}</option>
+                    { for tags.iter().map(|tag| {
+                        let value = format!("tag:{tag}");
+                        let selected = scope_value(scope) == value;
+                        html! {
+                            <option {selected} {value}>
+                                { format!("Tagged \"{tag}\"") }
+                            </option>
+                        }
+                    }) }
+                </select>
+                <span class="muted-note">{ format!("{card_count} cards will be {verb}.") }</span>
+            </div>
+            if let ExportScope::Selected(selected) = scope {
+                { card_picker(dataset, selected, &on_change) }
+            }
+        </>
+    }
+}
+
+#[function_component(ExportDialog)]
+pub fn export_dialog(props: &ExportDialogProps) -> Html {
+    let options = &props.options;
+    let scoped = scoped_dataset(&props.dataset, &options.scope);
+    let card_count = scoped.flashcards.len() + scoped.known_cards.len();
+
+    let on_format_change = {
+        let options = options.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Some(format) = select
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| EXPORT_FORMATS.get(index))
+                {
+                    on_change.emit(ExportOptions {
+                        format: *format,
+                        ..options.clone()
+                    });
+                }
+            }
+        })
+    };
+
+    let on_scope_change = {
+        let options = options.clone();
+        props
+            .on_change
+            .reform(move |scope: ExportScope| ExportOptions {
+                scope,
+                ..options.clone()
+            })
+    };
+
+    let on_file_stem_input = {
+        let options = options.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |event: InputEvent| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                on_change.emit(ExportOptions {
+                    file_stem: input.value(),
+                    ..options.clone()
+                });
+            }
+        })
+    };
+
+    let separator_change = |term: bool| {
+        let options = options.clone();
+        props.on_change.reform(move |separator: String| {
+            let mut options = options.clone();
+            if term {
+                options.quizlet.term = separator;
+            } else {
+                options.quizlet.row = separator;
+            }
+            options
+        })
+    };
+
+    let column_options = EXPORT_FIELDS.iter().map(|field| {
+        let checked = options.columns.contains(field);
+        let onchange = {
+            let options = options.clone();
+            let on_change = props.on_change.clone();
+            let field = *field;
+            Callback::from(move |_: Event| {
+                // Keep the columns in their usual order whatever order they are ticked in.
+                let columns = EXPORT_FIELDS
+                    .iter()
+                    .copied()
+                    .filter(|column| (*column == field) != options.columns.contains(column))
+                    .collect();
+                on_change.emit(ExportOptions {
+                    columns,
+                    ..options.clone()
+                });
+            })
+        };
+        html! {
+            <label class="export-option">
+                <input
+                    type="checkbox"
+                    {checked}
+                    disabled={!options.format.has_columns()}
+                    {onchange}
+                />
+                { field_label(*field) }
+            </label>
+        }
+    });
+
+    let extension = export_file_name("", options.format);
+
+    let share_field = match &props.share_link {
+        Some(link) => {
+            let select_link = Callback::from(|event: MouseEvent| {
+                if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                    input.select();
+                }
+            });
+            html! {
+                <>
+                    <div class="inline-create-row">
+                        <input
+                            type="text"
+                            class="text-input share-link-input"
+                            readonly=true
+                            value={link.clone()}
+                            onclick={select_link}
+                        />
+                        <button class="btn btn-secondary" onclick={props.on_copy_link.clone()}>
+                            {"Copy"}
+                        </button>
+                    </div>
+                    if link.len() > LONG_LINK_LENGTH {
+                        <span class="import-decoding-warning">
+                            { format!(
+                                "This link is {} characters long and some email and chat apps may cut it \
+                                short. Share fewer cards or send a JSON export instead.",
+                                link.len()
+                            ) }
+                        </span>
+                    }
+                </>
+            }
+        }
+        None => html! {
+            <div class="panel-actions">
+                <button class="btn btn-secondary" onclick={props.on_share.clone()} disabled={card_count == 0}>
+                    {"Create Share Link"}
+                </button>
+            </div>
+        },
+    };
+
+    html! {
+        <div class="help-backdrop">
+            <section class="help-modal panel export-dialog">
+                <div class="help-modal-header">
+                    <h2 class="panel-title help-modal-title">{ format!("Export \"{}\"", props.dataset.name) }</h2>
+                    <button class="btn btn-secondary btn-small help-close-btn" onclick={props.on_close.clone()}>
+                        {"✕ Close"}
+                    </button>
+                </div>
+
+                <div class="export-field">
+                    <label class="input-label">{"Format"}</label>
+                    <select class="text-input" onchange={on_format_change}>
+                        { for EXPORT_FORMATS.iter().enumerate().map(|(index, format)| html! {
+                            <option value={index.to_string()} selected={*format == options.format}>
+                                { export_format_label(*format) }
+                            </option>
+                        }) }
+                    </select>
+                </div>
+
+                { scope_field(&props.dataset, &options.scope, "exported", on_scope_change) }
+
+                <div class="export-field">
+                    <span class="input-label">{"Columns"}</span>
+                    <div class="export-columns">
+                        { for column_options }
+                    </div>
+                    if !options.format.has_columns() {
+                        <span class="muted-note">{"This format always writes the same columns."}</span>
+                    }
+                </div>
+
+                if options.format == ExportFormat::Quizlet {
+                    <div class="export-field">
+                        <span class="input-label">{"Separators"}</span>
+                        <div class="import-dialect">
+                            <SeparatorChoice
+                                label="Between term and definition"
+                                presets={&TERM_SEPARATORS[..]}
+                                current={options.quizlet.term.clone()}
+                                on_change={separator_change(true)}
+                            />
+                            <SeparatorChoice
+                                label="Between cards"
+                                presets={&ROW_SEPARATORS[..]}
+                                current={options.quizlet.row.clone()}
+                                on_change={separator_change(false)}
+                            />
+                        </div>
+                        <span class="muted-note">{"Pick the same separators in Quizlet's Import box."}</span>
+                    </div>
+                }
+
+                <div class="export-field">
+                    <label class="input-label" for="export-file-name">{"File name"}</label>
+                    <div class="inline-create-row">
+                        <input
+                            id="export-file-name"
+                            type="text"
+                            class="text-input"
+                            value={options.file_stem.clone()}
+                            oninput={on_file_stem_input}
+                        />
+                        <span class="muted-note">{ extension }</span>
+                    </div>
+                </div>
+
+                <div class="export-field">
+                    <span class="input-label">{"Share link"}</span>
+                    <span class="muted-note">
+                        {"A link holding the chosen cards, which anyone can open to import them as a new \
+                        wordset. The cards travel inside the link and are never uploaded."}
+                    </span>
+                    { share_field }
+                </div>
+
+                <div class="form-actions">
+                    <button class="btn btn-primary" onclick={props.on_export.clone()} disabled={card_count == 0}>
+                        {"Export"}
+                    </button>
+                    <button class="btn btn-muted" onclick={props.on_close.clone()}>{"Cancel"}</button>
+                </div>
+            </section>
+        </div>
+    }
+}
@@ -30,6 +30,7 @@ pub fn help_panel(props: &HelpPanelProps) -> Html {
                         <ul class="help-list">
                             <li>{"Works entirely offline after the page loads."}</li>
                             <li>{"Progress is saved automatically between sessions."}</li>
+                            <li>{"Tabs showing the app stay in sync; edits made to the same wordset in two tabs at once are both kept."}</li>
                             <li>{"Supports Chinese characters, pinyin, and a translation field."}</li>
                             <li>{"Export your cards at any time as a CSV file from the Wordsets panel."}</li>
                         </ul>
@@ -57,10 +58,10 @@ pub fn help_panel(props: &HelpPanelProps) -> Html {
                             <div>
                                 <strong>{"Import a CSV file"}</strong>
                                 <p class="help-text">
-                                    {"After creating a new empty wordset, an Import option appears inside the Wordsets panel. Click "}
+                                    {"Select a wordset and an Import option appears inside the Wordsets panel. Click "}
                                     <em>{"Choose File"}</em>
                                     {" and select a CSV file. \
-                                    Each row should have up to four columns:"}
+                                    Without a header row, columns are read in this order:"}
                                 </p>
                                 <div class="csv-format-block">
                                     <code>{"word, pinyin, translation, known"}</code>
@@ -75,10 +76,94 @@ pub fn help_panel(props: &HelpPanelProps) -> Html {
                                 <div class="csv-format-block">
                                     <code>{"你好,nǐ hǎo,Hello,false"}</code>
                                 </div>
+                                <p class="help-text">
+                                    {"Tab-separated (TSV) files, such as Anki or Pleco exports, and semicolon-separated \
+                                    spreadsheet exports work too. The separator and quote character are detected \
+                                    automatically and can be changed in the preview if the columns look wrong."}
+                                </p>
+                                <p class="help-text">
+                                    {"Files do not have to be UTF-8: wordlists saved as GBK, GB18030, Big5 or UTF-16 \
+                                    are recognised and decoded, and the preview shows the first lines of text. \
+                                    If the characters look garbled, pick another encoding there."}
+                                </p>
+                                <p class="help-text">
+                                    {"Before anything is imported, a preview shows the first rows of the file. \
+                                    A header row (e.g. "}
+                                    <code>{"Hanzi, Pinyin, English"}</code>
+                                    {") is detected automatically, and you can assign each column to a card field \
+                                    or ignore it. The mapping is remembered for the next import into the same wordset."}
+                                </p>
+                                <p class="help-text">
+                                    {"The preview also lists every problem found: rows without a word are skipped, \
+                                    and rows with a missing translation, unusual pinyin, an unrecognised known value \
+                                    or a duplicate word are flagged with their line number. \
+                                    Nothing is written until you confirm the import."}
+                                </p>
+                                <p class="help-text">
+                                    {"Excel workbooks (.xlsx) can be imported directly, which avoids the encoding problems \
+                                    of saving as CSV. If the workbook has several worksheets, pick the one to import in \
+                                    the preview."}
+                                </p>
+                                <p class="help-text">
+                                    {"Instead of choosing a file you can click "}
+                                    <em>{"Paste Rows"}</em>
+                                    {" and paste lines copied from a spreadsheet or document, or drop files onto the \
+                                    Wordsets panel. When several files are dropped or chosen at once, each is previewed \
+                                    in turn and goes into a new wordset named after it; untick the option in the preview \
+                                    to add the remaining files to the current wordset instead."}
+                                </p>
+                                <p class="help-text">
+                                    {"Markdown notes (.md files, or Markdown pasted with Paste Rows) are read from their \
+                                    tables, with the header row mapped to card fields like any other file. Notes without \
+                                    a table can list words as bullets such as "}
+                                    <code>{"- 你好 — nǐ hǎo — hello"}</code>
+                                    {"; a ticked task box ("}
+                                    <code>{"- [x] …"}</code>
+                                    {") marks the card as known."}
+                                </p>
+                                <p class="help-text">
+                                    {"To bring over a Quizlet set, use Export on the set's page in Quizlet and save the text \
+                                    as a .txt file, or paste it with Paste Rows. Exports that use Tab and New line with \
+                                    terms like 字 (zì) are recognised automatically; otherwise tick "}
+                                    <em>{"Quizlet export"}</em>
+                                    {" in the preview and pick the same separators you chose in Quizlet, typing custom \
+                                    ones with \\n for a line break. Pinyin in brackets after the term becomes the card's \
+                                    pinyin."}
+                                </p>
+                                <p class="help-text">
+                                    {"A wordset JSON file exported from this app is added as a new wordset exactly as it \
+                                    was, with its progress, tags and column mapping. Files from a newer version of the \
+                                    app, or with missing or inconsistent fields, are refused with a message saying why. \
+                                    Wordset bundles (.zip) are read the same way."}
+                                </p>
+                                <p class="help-text">
+                                    {"To bring over an Anki deck, click "}
+                                    <em>{"Import Anki Deck"}</em>
+                                    {" and choose an .apkg file. A new wordset named after the deck is created; \
+                                    note fields are matched to word, pinyin and translation by name, note tags are kept, \
+                                    and cards that are mature in Anki (reviewed at intervals of 21 days or more) \
+                                    are marked as known unless you set the Known column to Ignore. Decks from Anki 2.1.50 \
+                                    or newer must be exported with \"Support older Anki versions\" checked."}
+                                </p>
+                                <p class="help-text">
+                                    {"Pleco flashcard exports (File → Import/Export → Export Cards, as text) are recognised \
+                                    when imported. Numbered pinyin such as ni3 hao3 becomes nǐ hǎo. The cards can go into \
+                                    a new wordset, one wordset per Pleco category, or the current wordset; outside \
+                                    wordsets of their own, categories are kept as tags. If an ordinary tab-separated file is \
+                                    taken for a Pleco export, untick Pleco flashcards to read it as columns."}
+                                </p>
                                 <p class="help-text help-text-muted">
-                                    {"The file does not need a header row. \
-                                    Import is only available while the wordset is empty, \
-                                    so it cannot overwrite cards you have already added."}
+                                    {"When the wordset already has cards, choose how words that exist in both are handled: \
+                                    skip them, update their pinyin and translation from the file, or add every row anyway. \
+                                    A summary of added, updated and skipped cards is shown afterwards, and the import can be undone."}
+                                </p>
+                                <p class="help-text help-text-muted">
+                                    {"If you keep the master list outside the app, import the updated file and choose "}
+                                    <em>{"Sync with this file"}</em>
+                                    {". Cards are matched by word, and the preview lists the words that will be added, \
+                                    changed or removed before you click Apply Changes. Cards that did not change keep their \
+                                    progress, progress only changes when the file has a Known column, and removed cards go \
+                                    to the trash."}
                                 </p>
                             </div>
                         </div>
@@ -92,6 +177,14 @@ pub fn help_panel(props: &HelpPanelProps) -> Html {
                                     <em>{"Add New Flashcard"}</em>
                                     {" in the Study Controls panel to add a single card without a CSV file."}
                                 </p>
+                                <p class="help-text">
+                                    {"Click "}
+                                    <em>{"Load CC-CEDICT"}</em>
+                                    {" in the form and choose the CC-CEDICT download (the .zip or the .u8 file inside it) \
+                                    to get suggestions: as you type a word, its dictionary readings are listed and clicking \
+                                    one fills in pinyin and translation. A word with a single reading is filled in directly. \
+                                    The dictionary is kept in the browser, so it only needs to be loaded once."}
+                                </p>
                             </div>
                         </div>
 
@@ -109,6 +202,15 @@ pub fn help_panel(props: &HelpPanelProps) -> Html {
                                     <em>{"Randomize"}</em>
                                     {" to shuffle the order."}
                                 </p>
+                                <p class="help-text">
+                                    {"Made a mistake? "}
+                                    <em>{"Undo"}</em>
+                                    {" and "}
+                                    <em>{"Redo"}</em>
+                                    {" in the Study Controls panel (or Ctrl+Z / Ctrl+Shift+Z) revert \
+                                    adding, deleting, marking, restoring, shuffling, importing and \
+                                    renaming, including deleting a whole wordset."}
+                                </p>
                             </div>
                         </div>
 
@@ -123,7 +225,7 @@ pub fn help_panel(props: &HelpPanelProps) -> Html {
                                     {" to move a card back to the Flashcards section for further practice, \
                                     or "}
                                     <em>{"Delete"}</em>
-                                    {" to remove it permanently."}
+                                    {" to move it to the trash."}
                                 </p>
                                 <p class="help-text">
                                     {"Click "}
@@ -156,14 +258,128 @@ pub fn help_panel(props: &HelpPanelProps) -> Html {
 
                         <div class="help-step">
                             <span class="help-step-number">{"7"}</span>
+                            <div>
+                                <strong>{"Trash"}</strong>
+                                <p class="help-text">
+                                    {"Deleted wordsets and cards are kept in the "}
+                                    <em>{"Trash"}</em>
+                                    {" (button at the top of the page) where they can be restored \
+                                    or deleted forever; deleting forever cannot be undone. Cards whose wordset no longer \
+                                    exists are restored into the selected wordset. Items older than the chosen retention \
+                                    period (30 days by default) are purged automatically."}
+                                </p>
+                            </div>
+                        </div>
+
+                        <div class="help-step">
+                            <span class="help-step-number">{"8"}</span>
+                            <div>
+                                <strong>{"Snapshots"}</strong>
+                                <p class="help-text">
+                                    {"The app keeps hourly and daily snapshots of all wordsets in your browser, \
+                                    stored apart from the wordsets so they never take the space the wordsets need. \
+                                    Open "}
+                                    <em>{"Snapshots"}</em>
+                                    {" at the top of the page to see card counts per snapshot and roll back \
+                                    a single wordset or everything, for example after a bad import."}
+                                </p>
+                            </div>
+                        </div>
+
+                        <div class="help-step">
+                            <span class="help-step-number">{"9"}</span>
                             <div>
                                 <strong>{"Export"}</strong>
                                 <p class="help-text">
                                     {"Click "}
-                                    <em>{"Export Flashcards"}</em>
-                                    {" in the Wordsets panel to save all cards \
-                                    (including known/unknown status) as a CSV file for backup or sharing. \
-                                    The export button is available whenever a wordset is selected."}
+                                    <em>{"Export Flashcards…"}</em>
+                                    {" in the Wordsets panel to open the export dialog. It is available whenever a \
+                                    wordset is selected."}
+                                </p>
+                                <p class="help-text">
+                                    {"Choose which cards to include: all of them, only unknown or only known cards, \
+                                    cards with a given tag, or cards you tick one by one. For CSV-style files and Excel \
+                                    workbooks you can also pick the columns; a file with other columns than the usual \
+                                    word, pinyin, translation and known starts with a header row so it imports again \
+                                    correctly. The file name defaults to the wordset name and today's date."}
+                                </p>
+                                <p class="help-text">
+                                    {"The format list also switches the separator: comma for CSV, \
+                                    tab for a .tsv file, or semicolon and pipe for spreadsheets that expect them."}
+                                </p>
+                                <p class="help-text">
+                                    {"Choose "}
+                                    <em>{"Quizlet (import text)"}</em>
+                                    {" to create a Quizlet set: choose the separators between term and definition and \
+                                    between cards, then paste the file's contents into Quizlet's Import box with the \
+                                    same separators. Terms are written as word followed by pinyin in brackets."}
+                                </p>
+                                <p class="help-text">
+                                    {"Choose "}
+                                    <em>{"Wordset JSON (lossless)"}</em>
+                                    {" to back up a wordset or move it to another browser: the file keeps every card, \
+                                    its known status and tags, the study order and the import column mapping, and can \
+                                    be imported again with Import Flashcards."}
+                                </p>
+                                <p class="help-text">
+                                    {"Choose "}
+                                    <em>{"Wordset bundle (.zip)"}</em>
+                                    {" for the same wordset JSON packed into a compressed zip file, the format that will \
+                                    also carry pictures and sound once cards can have them."}
+                                </p>
+                                <p class="help-text">
+                                    {"Choose "}
+                                    <em>{"Markdown table"}</em>
+                                    {" to paste a wordset into lesson notes: a table with a checkbox column for known \
+                                    cards that imports again unchanged."}
+                                </p>
+                                <p class="help-text">
+                                    {"Choose "}
+                                    <em>{"Excel workbook"}</em>
+                                    {" for an .xlsx file with a formatted header row, known cards highlighted and a \
+                                    Progress sheet counting known and still-learning cards."}
+                                </p>
+                                <p class="help-text">
+                                    {"Choose "}
+                                    <em>{"Anki (text import)"}</em>
+                                    {" to share a wordset with Anki users: the file opens with Anki's File → Import, \
+                                    creating Basic notes (word on the front, pinyin above the translation on the back) \
+                                    in a deck named after the wordset, with the card's tags. \
+                                    Cards you already know are tagged "}
+                                    <code>{"known"}</code>
+                                    {"."}
+                                </p>
+                                <p class="help-text">
+                                    {"Choose "}
+                                    <em>{"Pleco flashcards"}</em>
+                                    {" for a file Pleco can import, with the wordset as its category and pinyin \
+                                    written with tone numbers."}
+                                </p>
+                                <p class="help-text">
+                                    {"For paper flashcards click "}
+                                    <em>{"Print Cards…"}</em>
+                                    {", choose the card size, which cards to print and the fields on the front and back. \
+                                    The print view opens in a new tab with the browser's print dialog (if pop-ups are \
+                                    blocked it is downloaded as an .html file instead). Fronts and backs are on alternate \
+                                    pages: print double-sided, flipping on the long edge, and cut along the dashed lines."}
+                                </p>
+                                <p class="help-text">
+                                    {"To share cards without sending a file, click "}
+                                    <em>{"Create Share Link"}</em>
+                                    {" in the export dialog. The chosen cards are compressed into the link itself and \
+                                    never uploaded. Whoever opens the link sees a preview and can import the cards as a \
+                                    new wordset; their own wordsets are not changed. Very long links may be cut short by \
+                                    email and chat apps, so share large wordsets as JSON files instead."}
+                                </p>
+                                <p class="help-text">
+                                    {"To move a wordset to your phone or another computer, click "}
+                                    <em>{"Show QR Codes…"}</em>
+                                    {" and, on the other device, "}
+                                    <em>{"Scan QR Codes…"}</em>
+                                    {". Larger wordsets take a series of codes, which cycle on screen and can be read in \
+                                    any order; a wordset that fits in one code can also be opened with the phone's camera \
+                                    app. Browsers that cannot read QR codes from the camera let you paste the text of \
+                                    codes read with another app instead."}
                                 </p>
                             </div>
                         </div>
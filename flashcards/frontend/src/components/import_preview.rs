@@ -0,0 +1,713 @@
+use encoding_rs::Encoding;
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, InputEvent, MouseEvent};
+use yew::prelude::*;
+
+use crate::csv_io::{delimiter_label, quote_label, CsvDialect, DELIMITERS, QUOTES};
+use crate::encoding::{encoding_label, ENCODINGS};
+use crate::file_sync::{diff_with_file, SyncDiff};
+use crate::import::{
+    build_report, column_count, field_label, file_stem, strategy_label, DelimitedText,
+    ImportFormat, ImportTarget, MergeStrategy, PendingImport, FIELD_CHOICES, MERGE_STRATEGIES,
+};
+use crate::model::{ColumnField, Flashcard};
+use crate::quizlet::{QuizletSeparators, ROW_SEPARATORS, TERM_SEPARATORS};
+
+const PREVIEW_ROWS: usize = 5;
+const TEXT_PREVIEW_LINES: usize = 5;
+const CARD_PREVIEW_ROWS: usize = 10;
+const LISTED_PROBLEMS: usize = 50;
+
+#[derive(Properties, PartialEq)]
+pub struct ImportPreviewProps {
+    pub pending: PendingImport,
+    pub on_column_change: Callback<(usize, ColumnField)>,
+    pub on_header_change: Callback<bool>,
+    pub on_strategy_change: Callback<MergeStrategy>,
+    pub on_dialect_change: Callback<CsvDialect>,
+    pub on_encoding_change: Callback<&'static Encoding>,
+    /// Switches Pleco imports between one wordset per category and categories as tags.
+    pub on_split_change: Callback<bool>,
+    pub on_target_change: Callback<ImportTarget>,
+    pub on_sheet_change: Callback<usize>,
+    /// Reads text as a Quizlet export with the given separators, or as delimited text when `None`.
+    pub on_quizlet_change: Callback<Option<QuizletSeparators>>,
+    /// Reads text as Pleco flashcards, or as delimited text when `false`.
+    pub on_pleco_change: Callback<bool>,
+    /// Name of the selected wordset, empty when none is selected.
+    pub current_dataset: String,
+    /// Files still waiting to be previewed after this one.
+    pub queued: usize,
+    /// Cards already in the target wordset, unknown ones first; merge options are
+    /// shown when there are any.
+    pub existing: Vec<Flashcard>,
+    pub on_confirm: Callback<MouseEvent>,
+    pub on_cancel: Callback<MouseEvent>,
+}
+
+/// Choice of the encoding the file is decoded with.
+fn encoding_select(text: &DelimitedText, props: &ImportPreviewProps) -> Html {
+    let on_encoding_change = {
+        let on_encoding_change = props.on_encoding_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Some(encoding) = select
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| ENCODINGS.get(index))
+                {
+                    on_encoding_change.emit(*encoding);
+                }
+            }
+        })
+    };
+
+    html! {
+        <label class="input-label">
+            {"Encoding "}
+            <select class="text-input import-column-select" onchange={on_encoding_change}>
+                { for ENCODINGS.iter().enumerate().map(|(index, encoding)| html! {
+                    <option value={index.to_string()} selected={*encoding == text.encoding}>
+                        { encoding_label(encoding) }
+                    </option>
+                }) }
+            </select>
+        </label>
+    }
+}
+
+fn decoding_warning(text: &DelimitedText) -> Html {
+    html! {
+        if text.decoding_errors {
+            <p class="import-decoding-warning">
+                { format!(
+                    "Some bytes are not valid {}; they are shown as �. Try another encoding if the text looks wrong.",
+                    encoding_label(text.encoding)
+                ) }
+            </p>
+        }
+    }
+}
+
+/// Encoding, separator and quote choices plus the first lines of decoded text.
+fn text_options(text: &DelimitedText, props: &ImportPreviewProps) -> Html {
+    let text_preview = text
+        .text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(TEXT_PREVIEW_LINES)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let on_delimiter_change = {
+        let on_dialect_change = props.on_dialect_change.clone();
+        let dialect = text.dialect;
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Some(delimiter) = select
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| DELIMITERS.get(index))
+                {
+                    on_dialect_change.emit(CsvDialect {
+                        delimiter: *delimiter,
+                        ..dialect
+                    });
+                }
+            }
+        })
+    };
+
+    let on_quote_change = {
+        let on_dialect_change = props.on_dialect_change.clone();
+        let dialect = text.dialect;
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Some(quote) = select
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| QUOTES.get(index))
+                {
+                    on_dialect_change.emit(CsvDialect {
+                        quote: *quote,
+                        ..dialect
+                    });
+                }
+            }
+        })
+    };
+
+    html! {
+        <>
+        <div class="import-dialect">
+            { encoding_select(text, props) }
+            <label class="input-label">
+                {"Separator "}
+                <select class="text-input import-column-select" onchange={on_delimiter_change}>
+                    { for DELIMITERS.iter().enumerate().map(|(index, delimiter)| html! {
+                        <option value={index.to_string()} selected={*delimiter == text.dialect.delimiter}>
+                            { delimiter_label(*delimiter) }
+                        </option>
+                    }) }
+                </select>
+            </label>
+            <label class="input-label">
+                {"Quotes "}
+                <select class="text-input import-column-select" onchange={on_quote_change}>
+                    { for QUOTES.iter().enumerate().map(|(index, quote)| html! {
+                        <option value={index.to_string()} selected={*quote == text.dialect.quote}>
+                            { quote_label(*quote) }
+                        </option>
+                    }) }
+                </select>
+            </label>
+        </div>
+        <pre class="import-text-preview">{ text_preview }</pre>
+        { decoding_warning(text) }
+        </>
+    }
+}
+
+/// Shows separators the way they are typed: `\n` for a line break and `\t` for a tab.
+fn escape_separator(separator: &str) -> String {
+    separator.replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn unescape_separator(typed: &str) -> String {
+    typed.replace("\\n", "\n").replace("\\t", "\t")
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SeparatorChoiceProps {
+    pub label: AttrValue,
+    pub presets: &'static [(&'static str, &'static str)],
+    pub current: String,
+    pub on_change: Callback<String>,
+}
+
+/// A choice among Quizlet's preset separators, with a text box for a custom one.
+/// Choosing Custom keeps the current separator until another one is typed.
+#[function_component(SeparatorChoice)]
+pub fn separator_choice(props: &SeparatorChoiceProps) -> Html {
+    let is_preset = props
+        .presets
+        .iter()
+        .any(|(_, separator)| *separator == props.current);
+    let custom = use_state(|| !is_preset);
+    let on_select = {
+        let custom = custom.clone();
+        let on_change = props.on_change.clone();
+        let presets = props.presets;
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                match select
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| presets.get(index))
+                {
+                    Some((_, separator)) => {
+                        custom.set(false);
+                        on_change.emit(separator.to_string());
+                    }
+                    None => custom.set(true),
+                }
+            }
+        })
+    };
+    let on_input = props.on_change.reform(|event: InputEvent| {
+        event
+            .target_dyn_into::<HtmlInputElement>()
+            .map(|input| unescape_separator(&input.value()))
+            .unwrap_or_default()
+    });
+    let custom = *custom || !is_preset;
+
+    html! {
+        <label class="input-label">
+            { format!("{} ", props.label) }
+            <select class="text-input import-column-select" onchange={on_select}>
+                { for props.presets.iter().enumerate().map(|(index, (name, separator))| html! {
+                    <option value={index.to_string()} selected={!custom && *separator == props.current}>{ *name }</option>
+                }) }
+                <option value="custom" selected={custom}>{"Custom"}</option>
+            </select>
+            if custom {
+                <input
+                    class="text-input quizlet-separator"
+                    placeholder="e.g. \\n\\n"
+                    value={escape_separator(&props.current)}
+                    oninput={on_input}
+                />
+            }
+        </label>
+    }
+}
+
+/// Switch between reading text as delimited columns and as a Quizlet export, with
+/// the Quizlet separators when it is read as one.
+fn quizlet_options(props: &ImportPreviewProps, separators: Option<&QuizletSeparators>) -> Html {
+    let on_toggle = {
+        let on_quizlet_change = props.on_quizlet_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                on_quizlet_change.emit(input.checked().then(QuizletSeparators::default));
+            }
+        })
+    };
+    let separator_change = |term: bool| {
+        let on_quizlet_change = props.on_quizlet_change.clone();
+        let separators = separators.cloned().unwrap_or_default();
+        Callback::from(move |separator: String| {
+            let mut separators = separators.clone();
+            if term {
+                separators.term = separator;
+            } else {
+                separators.row = separator;
+            }
+            on_quizlet_change.emit(Some(separators));
+        })
+    };
+
+    html! {
+        <>
+            <label class="import-header-toggle">
+                <input type="checkbox" checked={separators.is_some()} onchange={on_toggle} />
+                {" Quizlet export (pinyin written after the term in brackets is split off)"}
+            </label>
+            if let Some(separators) = separators {
+                <div class="import-dialect">
+                    <SeparatorChoice
+                        label="Between term and definition"
+                        presets={&TERM_SEPARATORS[..]}
+                        current={separators.term.clone()}
+                        on_change={separator_change(true)}
+                    />
+                    <SeparatorChoice
+                        label="Between cards"
+                        presets={&ROW_SEPARATORS[..]}
+                        current={separators.row.clone()}
+                        on_change={separator_change(false)}
+                    />
+                </div>
+            }
+        </>
+    }
+}
+
+/// Switch between reading text as delimited columns and as Pleco flashcards.
+fn pleco_option(props: &ImportPreviewProps, checked: bool) -> Html {
+    let on_toggle = {
+        let on_pleco_change = props.on_pleco_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                on_pleco_change.emit(input.checked());
+            }
+        })
+    };
+
+    html! {
+        <label class="import-header-toggle">
+            <input type="checkbox" {checked} onchange={on_toggle} />
+            {" Pleco flashcards (//Category lines, tone numbers converted to tone marks)"}
+        </label>
+    }
+}
+
+/// Checkbox sending the cards to a new wordset named after the file instead of the current one.
+fn target_option(props: &ImportPreviewProps) -> Html {
+    let pending = &props.pending;
+    let on_target_change = {
+        let on_target_change = props.on_target_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                on_target_change.emit(if input.checked() {
+                    ImportTarget::NewWordset
+                } else {
+                    ImportTarget::CurrentWordset
+                });
+            }
+        })
+    };
+
+    html! {
+        <label class="import-header-toggle">
+            <input
+                type="checkbox"
+                checked={pending.target == ImportTarget::NewWordset}
+                disabled={props.current_dataset.is_empty()}
+                onchange={on_target_change}
+            />
+            { if props.current_dataset.is_empty() {
+                format!(" Import into a new wordset \"{}\"", file_stem(&pending.file_name))
+            } else {
+                format!(
+                    " Import into a new wordset \"{}\" instead of \"{}\"",
+                    file_stem(&pending.file_name),
+                    props.current_dataset
+                )
+            } }
+        </label>
+    }
+}
+
+/// The changes syncing with the file would make, for review before applying them.
+fn sync_review(diff: &SyncDiff) -> Html {
+    let entry = |class: &'static str, text: String| html! { <li {class}>{ text }</li> };
+    let entries: Vec<Html> = diff
+        .added
+        .iter()
+        .map(|card| {
+            entry(
+                "sync-added",
+                format!("+ {} — {}", card.word, card.translation),
+            )
+        })
+        .chain(diff.changed.iter().map(|change| {
+            entry(
+                "sync-changed",
+                format!("~ {}: {}", change.after.word, change.describe()),
+            )
+        }))
+        .chain(diff.removed.iter().map(|card| {
+            entry(
+                "sync-removed",
+                format!("− {} — {}", card.word, card.translation),
+            )
+        }))
+        .collect();
+    let total = entries.len();
+
+    html! {
+        <>
+            <h4 class="import-preview-title">{"Changes"}</h4>
+            <p class="muted-note">
+                if diff.is_empty() {
+                    {"The wordset already matches the file."}
+                } else {
+                    { format!(
+                        "{}. Removed cards go to the trash and the rest keep their progress.",
+                        diff.describe()
+                    ) }
+                }
+            </p>
+            if total > 0 {
+                <ul class="import-problems sync-changes">
+                    { for entries.into_iter().take(LISTED_PROBLEMS) }
+                </ul>
+            }
+            if total > LISTED_PROBLEMS {
+                <p class="muted-note">{ format!("… and {} more changes", total - LISTED_PROBLEMS) }</p>
+            }
+        </>
+    }
+}
+
+#[function_component(ImportPreview)]
+pub fn import_preview(props: &ImportPreviewProps) -> Html {
+    let pending = &props.pending;
+    let rows = &pending.source.rows;
+    let columns = column_count(rows);
+    let report = build_report(&pending.source, &pending.mapping);
+    let merging = !props.existing.is_empty() && !pending.creates_wordsets();
+    let sync_diff = (merging && pending.strategy == MergeStrategy::SyncWithFile)
+        .then(|| diff_with_file(&props.existing, &pending.cards(), &pending.mapping.columns));
+
+    let on_header_change = {
+        let on_header_change = props.on_header_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                on_header_change.emit(input.checked());
+            }
+        })
+    };
+
+    let on_split_change = {
+        let on_split_change = props.on_split_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                on_split_change.emit(input.checked());
+            }
+        })
+    };
+
+    let on_sheet_change = {
+        let on_sheet_change = props.on_sheet_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Ok(index) = select.value().parse::<usize>() {
+                    on_sheet_change.emit(index);
+                }
+            }
+        })
+    };
+
+    let on_strategy_change = {
+        let on_strategy_change = props.on_strategy_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Some(strategy) = select
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| MERGE_STRATEGIES.get(index))
+                {
+                    on_strategy_change.emit(*strategy);
+                }
+            }
+        })
+    };
+
+    let column_selects = (0..columns).map(|column| {
+        let selected = pending
+            .mapping
+            .columns
+            .get(column)
+            .copied()
+            .unwrap_or(ColumnField::Ignore);
+        let on_column_change = props.on_column_change.clone();
+        let onchange = Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Some(field) = select
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| FIELD_CHOICES.get(index))
+                {
+                    on_column_change.emit((column, *field));
+                }
+            }
+        });
+        html! {
+            <th>
+                <select class="text-input import-column-select" {onchange}>
+                    { for FIELD_CHOICES.iter().enumerate().map(|(index, field)| html! {
+                        <option value={index.to_string()} selected={*field == selected}>
+                            { field_label(*field) }
+                        </option>
+                    }) }
+                </select>
+            </th>
+        }
+    });
+
+    let preview_rows = rows
+        .iter()
+        .take(PREVIEW_ROWS)
+        .enumerate()
+        .map(|(index, row)| {
+            let class = if index == 0 && pending.mapping.has_header {
+                "import-header-row"
+            } else {
+                ""
+            };
+            html! {
+                <tr {class}>
+                    { for (0..columns).map(|column| html! {
+                        <td>{ row.get(column).cloned().unwrap_or_default() }</td>
+                    }) }
+                </tr>
+            }
+        });
+
+    html! {
+        <div class="import-preview">
+            <h4 class="import-preview-title">{ format!("Import {}", pending.file_name) }</h4>
+            if props.queued > 0 {
+                <p class="muted-note">
+                    { format!(
+                        "{} more {} will be previewed after this one and imported the same way unless you change it.",
+                        props.queued,
+                        if props.queued == 1 { "file" } else { "files" }
+                    ) }
+                </p>
+            }
+            <p class="muted-note">
+                {"Choose which card field each column holds. Columns set to Ignore are skipped."}
+            </p>
+            { match &pending.format {
+                ImportFormat::Delimited(text) => html! {
+                    <>
+                        { text_options(text, props) }
+                        { quizlet_options(props, None) }
+                        { pleco_option(props, false) }
+                        { target_option(props) }
+                    </>
+                },
+                ImportFormat::Markdown { text } => html! {
+                    <>
+                        <div class="import-dialect">{ encoding_select(text, props) }</div>
+                        { decoding_warning(text) }
+                        <p class="muted-note">
+                            {"Markdown notes: the rows of every table are imported, or bullets split into word, \
+                            pinyin and translation at each dash. Ticked task boxes mark cards as known."}
+                        </p>
+                        { target_option(props) }
+                    </>
+                },
+                ImportFormat::Quizlet { text, separators } => html! {
+                    <>
+                        <div class="import-dialect">{ encoding_select(text, props) }</div>
+                        { decoding_warning(text) }
+                        { quizlet_options(props, Some(separators)) }
+                        { target_option(props) }
+                    </>
+                },
+                ImportFormat::Spreadsheet { sheets, selected } => html! {
+                    <>
+                        if sheets.len() > 1 {
+                            <div class="import-dialect">
+                                <label class="input-label">
+                                    {"Worksheet "}
+                                    <select class="text-input import-column-select" onchange={on_sheet_change}>
+                                        { for sheets.iter().enumerate().map(|(index, sheet)| html! {
+                                            <option value={index.to_string()} selected={index == *selected}>
+                                                { format!("{} ({} rows)", sheet.name, sheet.rows.rows.len()) }
+                                            </option>
+                                        }) }
+                                    </select>
+                                </label>
+                            </div>
+                        }
+                        { target_option(props) }
+                    </>
+                },
+                ImportFormat::Anki { deck_name } => html! {
+                    <p class="muted-note">
+                        { format!(
+                            "Anki deck \"{deck_name}\" will be imported into a new wordset. \
+                            The Known column marks cards that are mature in Anki; set it to Ignore to start every card as unknown."
+                        ) }
+                    </p>
+                },
+                ImportFormat::Pleco { text, split_categories, .. } => html! {
+                    <>
+                        <div class="import-dialect">{ encoding_select(text, props) }</div>
+                        { decoding_warning(text) }
+                        <p class="muted-note">
+                            {"Pleco flashcards are imported with tone numbers converted to tone marks. \
+                            Untick Pleco flashcards to read the file as ordinary columns instead."}
+                        </p>
+                        { pleco_option(props, true) }
+                        { target_option(props) }
+                        if pending.creates_wordsets() {
+                            <label class="import-header-toggle">
+                                <input type="checkbox" checked={*split_categories} onchange={on_split_change} />
+                                {" One wordset per Pleco category (otherwise categories become tags)"}
+                            </label>
+                        }
+                    </>
+                },
+            } }
+            <label class="import-header-toggle">
+                <input
+                    type="checkbox"
+                    checked={pending.mapping.has_header}
+                    onchange={on_header_change}
+                />
+                {" First row is a header"}
+            </label>
+            <div class="import-preview-table">
+                <table class="known-table">
+                    <tr>{ for column_selects }</tr>
+                    { for preview_rows }
+                </table>
+            </div>
+            if rows.len() > PREVIEW_ROWS {
+                <p class="muted-note">{ format!("… and {} more rows", rows.len() - PREVIEW_ROWS) }</p>
+            }
+
+            <h4 class="import-preview-title">{"Preview"}</h4>
+            <p class="import-summary">
+                <span class="status-badge-known">{ format!("{} accepted", report.accepted.len()) }</span>
+                <span class="status-badge-unknown">{ format!("{} with warnings", report.warning_count()) }</span>
+                <span class="status-badge-rejected">{ format!("{} rejected", report.rejected.len()) }</span>
+            </p>
+            <div class="import-preview-table">
+                <table class="known-table">
+                    <tr>
+                        <th>{"Line"}</th>
+                        <th>{"Word"}</th>
+                        <th>{"Pinyin"}</th>
+                        <th>{"Translation"}</th>
+                        <th>{"Known"}</th>
+                        <th>{"Tags"}</th>
+                    </tr>
+                    { for report.accepted.iter().take(CARD_PREVIEW_ROWS).map(|row| html! {
+                        <tr>
+                            <td>{ row.line }</td>
+                            <td>{ &row.card.word }</td>
+                            <td>{ row.card.pinyin.as_deref().unwrap_or_default() }</td>
+                            <td>{ &row.card.translation }</td>
+                            <td>{ if row.card.known { "Yes" } else { "No" } }</td>
+                            <td>{ row.card.tags.join(" ") }</td>
+                        </tr>
+                    }) }
+                </table>
+            </div>
+            if report.accepted.len() > CARD_PREVIEW_ROWS {
+                <p class="muted-note">{ format!("… and {} more cards", report.accepted.len() - CARD_PREVIEW_ROWS) }</p>
+            }
+
+            if !report.rejected.is_empty() || report.warning_count() > 0 {
+                <h4 class="import-preview-title">{"Problems"}</h4>
+                <ul class="import-problems">
+                    { for report.rejected.iter().map(|row| html! {
+                        <li class="import-problem-rejected">
+                            { format!("Line {}: skipped — {}", row.line, row.reason) }
+                        </li>
+                    }).chain(report.accepted.iter().flat_map(|row| {
+                        row.warnings.iter().map(move |warning| html! {
+                            <li class="import-problem-warning">
+                                { format!("Line {} ({}): {}", row.line, row.card.word, warning) }
+                            </li>
+                        })
+                    })).take(LISTED_PROBLEMS) }
+                </ul>
+            }
+
+            if merging {
+                <div class="import-strategy">
+                    <label class="input-label" for="import-strategy">
+                        { format!("This wordset already has {} cards", props.existing.len()) }
+                    </label>
+                    <select id="import-strategy" class="text-input" onchange={on_strategy_change}>
+                        { for MERGE_STRATEGIES.iter().enumerate().map(|(index, strategy)| html! {
+                            <option value={index.to_string()} selected={*strategy == pending.strategy}>
+                                { strategy_label(*strategy) }
+                            </option>
+                        }) }
+                    </select>
+                </div>
+            }
+            if let Some(diff) = &sync_diff {
+                { sync_review(diff) }
+            }
+
+            <div class="form-actions">
+                if let Some(diff) = &sync_diff {
+                    <button
+                        class="btn btn-primary"
+                        disabled={diff.is_empty()}
+                        onclick={props.on_confirm.clone()}
+                    >
+                        {"Apply Changes"}
+                    </button>
+                } else {
+                    <button
+                        class="btn btn-primary"
+                        disabled={report.accepted.is_empty()}
+                        onclick={props.on_confirm.clone()}
+                    >
+                        { format!("Import {} Cards", report.accepted.len()) }
+                    </button>
+                }
+                <button class="btn btn-muted" onclick={props.on_cancel.clone()}>
+                    { if props.queued > 0 { "Skip File" } else { "Cancel" } }
+                </button>
+            </div>
+        </div>
+    }
+}
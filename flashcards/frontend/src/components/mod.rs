@@ -1,6 +1,14 @@
 pub mod add_flashcard_form;
 pub mod dataset_panel;
+pub mod export_dialog;
 pub mod flashcard_view;
 pub mod help_panel;
+pub mod import_preview;
 pub mod known_cards_table;
+pub mod print_dialog;
+pub mod qr_dialog;
+pub mod qr_scanner;
+pub mod shared_wordset_preview;
+pub mod snapshot_panel;
 pub mod study_toolbar;
+pub mod trash_panel;
@@ -0,0 +1,150 @@
+use web_sys::{Event, HtmlSelectElement, MouseEvent};
+use yew::prelude::*;
+
+use crate::components::export_dialog::scope_field;
+use crate::export::{scoped_dataset, ExportScope};
+use crate::import::field_label;
+use crate::model::{ColumnField, Dataset};
+use crate::print::{card_size_label, PrintOptions, CARD_SIZES, PRINT_FIELDS};
+
+#[derive(Properties, PartialEq)]
+pub struct PrintDialogProps {
+    /// The wordset being printed, with its current cards.
+    pub dataset: Dataset,
+    pub options: PrintOptions,
+    pub on_change: Callback<PrintOptions>,
+    pub on_print: Callback<MouseEvent>,
+    pub on_close: Callback<MouseEvent>,
+}
+
+/// Checkboxes choosing the fields printed on one side of the cards.
+fn side_fields(label: &str, fields: &[ColumnField], on_change: Callback<Vec<ColumnField>>) -> Html {
+    html! {
+        <div class="export-field">
+            <span class="input-label">{ label }</span>
+            <div class="export-columns">
+                { for PRINT_FIELDS.iter().map(|field| {
+                    let checked = fields.contains(field);
+                    let onchange = {
+                        let fields = fields.to_vec();
+                        let field = *field;
+                        on_change.reform(move |_: Event| {
+                            // A field ticked last is printed after the ones already chosen.
+                            let mut fields = fields.clone();
+                            if checked {
+                                fields.retain(|existing| *existing != field);
+                            } else {
+                                fields.push(field);
+                            }
+                            fields
+                        })
+                    };
+                    html! {
+                        <label class="export-option">
+                            <input type="checkbox" {checked} {onchange} />
+                            { field_label(*field) }
+                        </label>
+                    }
+                }) }
+            </div>
+        </div>
+    }
+}
+
+#[function_component(PrintDialog)]
+pub fn print_dialog(props: &PrintDialogProps) -> Html {
+    let options = &props.options;
+    let scoped = scoped_dataset(&props.dataset, &options.scope);
+    let card_count = scoped.flashcards.len() + scoped.known_cards.len();
+
+    let on_size_change = {
+        let options = options.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Some(size) = select
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| CARD_SIZES.get(index))
+                {
+                    on_change.emit(PrintOptions {
+                        size: *size,
+                        ..options.clone()
+                    });
+                }
+            }
+        })
+    };
+
+    let on_scope_change = {
+        let options = options.clone();
+        props
+            .on_change
+            .reform(move |scope: ExportScope| PrintOptions {
+                scope,
+                ..options.clone()
+            })
+    };
+    let on_front_change = {
+        let options = options.clone();
+        props
+            .on_change
+            .reform(move |front: Vec<ColumnField>| PrintOptions {
+                front,
+                ..options.clone()
+            })
+    };
+    let on_back_change = {
+        let options = options.clone();
+        props
+            .on_change
+            .reform(move |back: Vec<ColumnField>| PrintOptions {
+                back,
+                ..options.clone()
+            })
+    };
+
+    html! {
+        <div class="help-backdrop">
+            <section class="help-modal panel export-dialog">
+                <div class="help-modal-header">
+                    <h2 class="panel-title help-modal-title">{ format!("Print \"{}\"", props.dataset.name) }</h2>
+                    <button class="btn btn-secondary btn-small help-close-btn" onclick={props.on_close.clone()}>
+                        {"✕ Close"}
+                    </button>
+                </div>
+                <p class="help-text help-text-muted">
+                    {"Opens a printable A4 sheet with the fronts of the cards on one page and their backs \
+                    on the next. Print double-sided, flipping on the long edge, and cut along the dashed lines."}
+                </p>
+
+                <div class="export-field">
+                    <label class="input-label">{"Card size"}</label>
+                    <select class="text-input" onchange={on_size_change}>
+                        { for CARD_SIZES.iter().enumerate().map(|(index, size)| html! {
+                            <option value={index.to_string()} selected={*size == options.size}>
+                                { card_size_label(*size) }
+                            </option>
+                        }) }
+                    </select>
+                </div>
+
+                { scope_field(&props.dataset, &options.scope, "printed", on_scope_change) }
+                { side_fields("Front", &options.front, on_front_change) }
+                { side_fields("Back", &options.back, on_back_change) }
+
+                <div class="form-actions">
+                    <button
+                        class="btn btn-primary"
+                        onclick={props.on_print.clone()}
+                        disabled={card_count == 0 || (options.front.is_empty() && options.back.is_empty())}
+                    >
+                        {"Open Print View"}
+                    </button>
+                    <button class="btn btn-muted" onclick={props.on_close.clone()}>{"Cancel"}</button>
+                </div>
+            </section>
+        </div>
+    }
+}
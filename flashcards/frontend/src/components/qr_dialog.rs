@@ -0,0 +1,105 @@
+use gloo_timers::callback::Timeout;
+use web_sys::{Event, MouseEvent};
+use yew::prelude::*;
+
+use crate::components::export_dialog::scope_field;
+use crate::export::{scoped_dataset, ExportScope};
+use crate::model::Dataset;
+use crate::qr::{qr_payloads, qr_svg};
+use crate::share::current_page_url;
+
+/// Time each code of a series stays on screen while cycling through them.
+const CODE_INTERVAL_MS: u32 = 1_500;
+
+#[derive(Properties, PartialEq)]
+pub struct QrDialogProps {
+    /// The wordset being transferred, with its current cards.
+    pub dataset: Dataset,
+    pub scope: ExportScope,
+    pub on_scope_change: Callback<ExportScope>,
+    pub on_close: Callback<MouseEvent>,
+}
+
+#[function_component(QrDialog)]
+pub fn qr_dialog(props: &QrDialogProps) -> Html {
+    let codes = use_memo(
+        (props.dataset.clone(), props.scope.clone()),
+        |(dataset, scope)| qr_payloads(&current_page_url(), &scoped_dataset(dataset, scope)),
+    );
+    let index = use_state_eq(|| 0_usize);
+    let cycling = use_state_eq(|| true);
+
+    let count = codes.as_ref().as_ref().map_or(0, Vec::len);
+    let shown = if *index < count { *index } else { 0 };
+
+    {
+        let index = index.clone();
+        use_effect_with((shown, count, *cycling), move |&(shown, count, cycling)| {
+            let timeout = (cycling && count > 1)
+                .then(|| Timeout::new(CODE_INTERVAL_MS, move || index.set((shown + 1) % count)));
+            move || drop(timeout)
+        });
+    }
+
+    let step = |offset: usize| {
+        let index = index.clone();
+        let cycling = cycling.clone();
+        Callback::from(move |_: MouseEvent| {
+            cycling.set(false);
+            index.set((shown + offset) % count.max(1));
+        })
+    };
+    let on_cycling_change = {
+        let cycling = cycling.clone();
+        Callback::from(move |_: Event| cycling.set(!*cycling))
+    };
+
+    let code = match codes.as_ref() {
+        Ok(codes) => match codes.get(shown).map(|text| qr_svg(text)) {
+            Some(Ok(svg)) => html! {
+                <div class="qr-code">{ Html::from_html_unchecked(AttrValue::from(svg)) }</div>
+            },
+            Some(Err(error)) => html! { <p class="import-decoding-warning">{ error }</p> },
+            None => html! {},
+        },
+        Err(error) => html! { <p class="import-decoding-warning">{ error.clone() }</p> },
+    };
+
+    html! {
+        <div class="help-backdrop">
+            <section class="help-modal panel export-dialog">
+                <div class="help-modal-header">
+                    <h2 class="panel-title help-modal-title">{ format!("QR codes for \"{}\"", props.dataset.name) }</h2>
+                    <button class="btn btn-secondary btn-small help-close-btn" onclick={props.on_close.clone()}>
+                        {"✕ Close"}
+                    </button>
+                </div>
+                <p class="help-text help-text-muted">
+                    if count > 1 {
+                        {"On the other device, open this app, click Scan QR Codes… and point the camera at \
+                        the screen until every code has been read. The codes can be read in any order."}
+                    } else {
+                        {"Scan the code with the camera app of the other device to open the wordset there, \
+                        or click Scan QR Codes… in this app on that device."}
+                    }
+                </p>
+
+                { scope_field(&props.dataset, &props.scope, "transferred", props.on_scope_change.clone()) }
+
+                { code }
+
+                if count > 1 {
+                    <div class="qr-controls">
+                        <button class="btn btn-secondary btn-small" onclick={step(count - 1)}>{"◀ Previous"}</button>
+                        <span class="status-chip">{ format!("Code {} of {count}", shown + 1) }</span>
+                        <button class="btn btn-secondary btn-small" onclick={step(1)}>{"Next ▶"}</button>
+                        <label class="export-option">
+                            <input type="checkbox" checked={*cycling} onchange={on_cycling_change} />
+                            {"Cycle through the codes"}
+                        </label>
+                    </div>
+                }
+            </section>
+        </div>
+    }
+}
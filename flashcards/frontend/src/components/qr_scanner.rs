@@ -0,0 +1,191 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use gloo_timers::future::TimeoutFuture;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlTextAreaElement, HtmlVideoElement, InputEvent, MediaStream, MouseEvent};
+use yew::prelude::*;
+
+use crate::model::Dataset;
+use crate::qr::{barcode_detector, detect_codes, start_camera, stop_camera, QrAssembly};
+
+/// Pause between two looks at the camera picture.
+const SCAN_INTERVAL_MS: u32 = 250;
+
+#[derive(Properties, PartialEq)]
+pub struct QrScannerProps {
+    /// Receives the wordset once every code of it has been read.
+    pub on_scanned: Callback<Dataset>,
+    pub on_close: Callback<MouseEvent>,
+}
+
+#[function_component(QrScanner)]
+pub fn qr_scanner(props: &QrScannerProps) -> Html {
+    let video = use_node_ref();
+    let assembly = use_mut_ref(QrAssembly::default);
+    let problem = use_state_eq(|| None::<String>);
+    let camera_problem = use_state_eq(|| None::<String>);
+    let pasted = use_state(String::new);
+    let update = use_force_update();
+
+    let add_code = {
+        let assembly = assembly.clone();
+        let problem = problem.clone();
+        let update = update.clone();
+        let on_scanned = props.on_scanned.clone();
+        Callback::from(move |text: String| {
+            let added = assembly.borrow_mut().add(&text);
+            match added {
+                Ok(true) => {
+                    problem.set(None);
+                    update.force_update();
+                    let finished = assembly.borrow().finish();
+                    match finished {
+                        Some(Ok(dataset)) => on_scanned.emit(dataset),
+                        Some(Err(error)) => problem.set(Some(error)),
+                        None => {}
+                    }
+                }
+                Ok(false) => {}
+                Err(error) => problem.set(Some(error)),
+            }
+        })
+    };
+
+    {
+        let video = video.clone();
+        let camera_problem = camera_problem.clone();
+        let add_code = add_code.clone();
+
+        use_effect_with((), move |_| {
+            let running = Rc::new(Cell::new(true));
+            let stream = Rc::new(RefCell::new(None::<MediaStream>));
+            {
+                let running = running.clone();
+                let stream = stream.clone();
+                spawn_local(async move {
+                    let Some(element) = video.cast::<HtmlVideoElement>() else {
+                        return;
+                    };
+                    let Some(detector) = barcode_detector() else {
+                        camera_problem.set(Some(
+                            "This browser cannot read QR codes from the camera. Scan the codes with \
+                            another app and paste their text below."
+                                .to_string(),
+                        ));
+                        return;
+                    };
+                    match start_camera(&element).await {
+                        Ok(started) if running.get() => *stream.borrow_mut() = Some(started),
+                        Ok(started) => return stop_camera(&started),
+                        Err(_) => {
+                            camera_problem.set(Some(
+                                "The camera could not be started. Allow this page to use the camera, \
+                                or paste the text of the codes below."
+                                    .to_string(),
+                            ));
+                            return;
+                        }
+                    }
+                    while running.get() {
+                        for text in detect_codes(&detector, &element).await {
+                            add_code.emit(text);
+                        }
+                        TimeoutFuture::new(SCAN_INTERVAL_MS).await;
+                    }
+                });
+            }
+            move || {
+                running.set(false);
+                if let Some(stream) = stream.borrow_mut().take() {
+                    stop_camera(&stream);
+                }
+            }
+        });
+    }
+
+    let on_pasted_input = {
+        let pasted = pasted.clone();
+        Callback::from(move |event: InputEvent| {
+            if let Some(area) = event.target_dyn_into::<HtmlTextAreaElement>() {
+                pasted.set(area.value());
+            }
+        })
+    };
+    let add_pasted = {
+        let pasted = pasted.clone();
+        let add_code = add_code.clone();
+        Callback::from(move |_: MouseEvent| {
+            // One code per line, so several can be pasted at once.
+            for line in pasted.lines().filter(|line| !line.trim().is_empty()) {
+                add_code.emit(line.to_string());
+            }
+            pasted.set(String::new());
+        })
+    };
+    let start_over = {
+        let assembly = assembly.clone();
+        let problem = problem.clone();
+        Callback::from(move |_: MouseEvent| {
+            *assembly.borrow_mut() = QrAssembly::default();
+            problem.set(None);
+            update.force_update();
+        })
+    };
+
+    let (scanned, total) = assembly.borrow().progress();
+
+    html! {
+        <div class="help-backdrop">
+            <section class="help-modal panel export-dialog">
+                <div class="help-modal-header">
+                    <h2 class="panel-title help-modal-title">{"Scan QR Codes"}</h2>
+                    <button class="btn btn-secondary btn-small help-close-btn" onclick={props.on_close.clone()}>
+                        {"✕ Close"}
+                    </button>
+                </div>
+                <p class="help-text help-text-muted">
+                    {"Show the codes with Show QR Codes… on the other device and point this device's \
+                    camera at them. Nothing is imported until you confirm."}
+                </p>
+
+                if let Some(message) = (*camera_problem).clone() {
+                    <p class="import-decoding-warning">{ message }</p>
+                } else {
+                    <video class="qr-video" ref={video} autoplay=true muted=true playsinline=true />
+                }
+
+                <p class="status-chip">
+                    if total == 0 {
+                        {"No codes read yet."}
+                    } else {
+                        { format!("Read {scanned} of {total} codes.") }
+                    }
+                </p>
+                if let Some(message) = (*problem).clone() {
+                    <p class="import-decoding-warning">{ message }</p>
+                }
+
+                <div class="export-field">
+                    <label class="input-label" for="qr-pasted-text">{"Text of scanned codes"}</label>
+                    <textarea
+                        id="qr-pasted-text"
+                        class="text-input"
+                        rows="3"
+                        value={(*pasted).clone()}
+                        oninput={on_pasted_input}
+                    />
+                </div>
+
+                <div class="form-actions">
+                    <button class="btn btn-primary" onclick={add_pasted} disabled={pasted.trim().is_empty()}>
+                        {"Add Pasted Codes"}
+                    </button>
+                    <button class="btn btn-muted" onclick={start_over} disabled={total == 0}>
+                        {"Start Over"}
+                    </button>
+                </div>
+            </section>
+        </div>
+    }
+}
@@ -0,0 +1,79 @@
+use web_sys::MouseEvent;
+use yew::prelude::*;
+
+use crate::model::Dataset;
+
+/// Cards listed in the preview before the rest are summarised.
+const PREVIEW_CARDS: usize = 10;
+
+#[derive(Properties, PartialEq)]
+pub struct SharedWordsetPreviewProps {
+    /// The wordset read from a share link or from QR codes.
+    pub dataset: Dataset,
+    pub on_import: Callback<MouseEvent>,
+    pub on_dismiss: Callback<MouseEvent>,
+}
+
+#[function_component(SharedWordsetPreview)]
+pub fn shared_wordset_preview(props: &SharedWordsetPreviewProps) -> Html {
+    let dataset = &props.dataset;
+    let total = dataset.flashcards.len() + dataset.known_cards.len();
+    let cards = dataset.flashcards.iter().chain(&dataset.known_cards);
+
+    html! {
+        <div class="help-backdrop">
+            <section class="help-modal panel shared-wordset">
+                <div class="help-modal-header">
+                    <h2 class="panel-title help-modal-title">{ format!("Shared wordset \"{}\"", dataset.name) }</h2>
+                    <button class="btn btn-secondary btn-small help-close-btn" onclick={props.on_dismiss.clone()}>
+                        {"✕ Close"}
+                    </button>
+                </div>
+                <p class="help-text">
+                    { format!(
+                        "It holds {total} cards, {} of them marked as known. Importing adds them as a \
+                        new wordset and leaves your own wordsets untouched.",
+                        dataset.known_cards.len()
+                    ) }
+                </p>
+
+                <table class="known-table">
+                    <thead>
+                        <tr>
+                            <th>{"Word"}</th>
+                            <th>{"Pinyin"}</th>
+                            <th>{"Translation"}</th>
+                            <th>{"Status"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for cards.take(PREVIEW_CARDS).map(|card| html! {
+                            <tr>
+                                <td>{ &card.word }</td>
+                                <td>{ card.pinyin.as_deref().unwrap_or_default() }</td>
+                                <td>{ &card.translation }</td>
+                                <td>
+                                    if card.known {
+                                        <span class="status-badge-known">{"Known"}</span>
+                                    } else {
+                                        <span class="status-badge-unknown">{"Unknown"}</span>
+                                    }
+                                </td>
+                            </tr>
+                        }) }
+                    </tbody>
+                </table>
+                if total > PREVIEW_CARDS {
+                    <p class="muted-note">{ format!("…and {} more cards.", total - PREVIEW_CARDS) }</p>
+                }
+
+                <div class="form-actions">
+                    <button class="btn btn-primary" onclick={props.on_import.clone()}>
+                        {"Import as New Wordset"}
+                    </button>
+                    <button class="btn btn-muted" onclick={props.on_dismiss.clone()}>{"Dismiss"}</button>
+                </div>
+            </section>
+        </div>
+    }
+}
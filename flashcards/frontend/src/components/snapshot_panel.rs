@@ -0,0 +1,107 @@
+use wasm_bindgen::JsValue;
+use web_sys::MouseEvent;
+use yew::prelude::*;
+
+use crate::model::{AutoSnapshot, SnapshotKind};
+use crate::snapshots::card_count;
+
+#[derive(Properties, PartialEq)]
+pub struct SnapshotPanelProps {
+    pub snapshots: Vec<AutoSnapshot>,
+    pub on_close: Callback<MouseEvent>,
+    pub on_take_snapshot: Callback<MouseEvent>,
+    pub on_restore_all: Callback<usize>,
+    pub on_restore_dataset: Callback<(usize, String)>,
+}
+
+fn format_taken_at(timestamp: f64) -> String {
+    js_sys::Date::new(&JsValue::from_f64(timestamp))
+        .to_locale_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+fn kind_label(kind: SnapshotKind) -> &'static str {
+    match kind {
+        SnapshotKind::Hourly => "Hourly",
+        SnapshotKind::Daily => "Daily",
+        SnapshotKind::Manual => "Manual",
+    }
+}
+
+#[function_component(SnapshotPanel)]
+pub fn snapshot_panel(props: &SnapshotPanelProps) -> Html {
+    let snapshot_items = props.snapshots.iter().enumerate().rev().map(|(index, snapshot)| {
+        let on_restore_all = props.on_restore_all.clone();
+        let total: usize = snapshot.datasets.iter().map(card_count).sum();
+        let dataset_rows = snapshot.datasets.iter().map(|dataset| {
+            let on_restore_dataset = props.on_restore_dataset.clone();
+            let name = dataset.name.clone();
+            html! {
+                <tr>
+                    <td>{ &dataset.name }</td>
+                    <td>{ format!("{} cards", card_count(dataset)) }</td>
+                    <td>{ format!("{} known", dataset.known_cards.len()) }</td>
+                    <td class="known-actions-cell">
+                        <button
+                            class="btn btn-secondary btn-small"
+                            onclick={Callback::from(move |_| on_restore_dataset.emit((index, name.clone())))}
+                        >
+                            {"Roll Back Wordset"}
+                        </button>
+                    </td>
+                </tr>
+            }
+        });
+
+        html! {
+            <div class="snapshot-item">
+                <div class="snapshot-header">
+                    <span class="status-badge-known">{ kind_label(snapshot.kind) }</span>
+                    <strong>{ format_taken_at(snapshot.taken_at) }</strong>
+                    <span class="muted-note">
+                        { format!("{} wordsets, {} cards", snapshot.datasets.len(), total) }
+                    </span>
+                    <button
+                        class="btn btn-danger btn-small"
+                        onclick={Callback::from(move |_| on_restore_all.emit(index))}
+                    >
+                        {"Roll Back Everything"}
+                    </button>
+                </div>
+                <table class="known-table">
+                    { for dataset_rows }
+                </table>
+            </div>
+        }
+    });
+
+    html! {
+        <div class="help-backdrop">
+            <section class="help-modal panel">
+                <div class="help-modal-header">
+                    <h2 class="panel-title help-modal-title">{"Snapshots"}</h2>
+                    <button class="btn btn-secondary btn-small help-close-btn" onclick={props.on_close.clone()}>
+                        {"✕ Close"}
+                    </button>
+                </div>
+
+                <p class="help-text help-text-muted">
+                    {"Copies of all wordsets are saved automatically every hour (last 24 kept) \
+                    and every day (last 7 kept) while you use the app. \
+                    Rolling back can itself be undone."}
+                </p>
+                <div class="panel-actions">
+                    <button class="btn btn-primary" onclick={props.on_take_snapshot.clone()}>{"Take Snapshot Now"}</button>
+                </div>
+
+                if props.snapshots.is_empty() {
+                    <p class="muted-note">{"No snapshots yet."}</p>
+                } else {
+                    <div class="snapshot-list">
+                        { for snapshot_items }
+                    </div>
+                }
+            </section>
+        </div>
+    }
+}
@@ -9,10 +9,25 @@ pub struct StudyToolbarProps {
     pub on_toggle_direction: Callback<MouseEvent>,
     pub on_randomize: Callback<MouseEvent>,
     pub on_open_add: Callback<MouseEvent>,
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+    pub on_undo: Callback<MouseEvent>,
+    pub on_redo: Callback<MouseEvent>,
 }
 
 #[function_component(StudyToolbar)]
 pub fn study_toolbar(props: &StudyToolbarProps) -> Html {
+    let undo_title = props
+        .undo_label
+        .as_ref()
+        .map(|label| format!("Undo: {label} (Ctrl+Z)"))
+        .unwrap_or_else(|| "Nothing to undo".to_string());
+    let redo_title = props
+        .redo_label
+        .as_ref()
+        .map(|label| format!("Redo: {label} (Ctrl+Shift+Z)"))
+        .unwrap_or_else(|| "Nothing to redo".to_string());
+
     html! {
         <section class="toolbar panel">
             <h3 class="panel-title">{"Study Controls"}</h3>
@@ -29,6 +44,22 @@ pub fn study_toolbar(props: &StudyToolbarProps) -> Html {
                 {"Randomize"}
             </button>
             <button class="btn btn-primary" onclick={props.on_open_add.clone()}>{"Add New Flashcard"}</button>
+            <button
+                class="btn btn-secondary"
+                title={undo_title}
+                disabled={props.undo_label.is_none()}
+                onclick={props.on_undo.clone()}
+            >
+                {"Undo"}
+            </button>
+            <button
+                class="btn btn-secondary"
+                title={redo_title}
+                disabled={props.redo_label.is_none()}
+                onclick={props.on_redo.clone()}
+            >
+                {"Redo"}
+            </button>
             </div>
         </section>
     }
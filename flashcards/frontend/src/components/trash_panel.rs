@@ -0,0 +1,159 @@
+use wasm_bindgen::JsValue;
+use web_sys::{Event, HtmlSelectElement, MouseEvent};
+use yew::prelude::*;
+
+use crate::model::Trash;
+use crate::trash::RETENTION_CHOICES;
+
+#[derive(Properties, PartialEq)]
+pub struct TrashPanelProps {
+    pub trash: Trash,
+    /// Names of the existing wordsets, which trashed cards are restored into.
+    pub dataset_names: Vec<String>,
+    /// Where cards whose wordset no longer exists are restored, empty when none is selected.
+    pub current_dataset: String,
+    pub on_close: Callback<MouseEvent>,
+    pub on_restore_dataset: Callback<usize>,
+    pub on_purge_dataset: Callback<usize>,
+    pub on_restore_card: Callback<usize>,
+    pub on_purge_card: Callback<usize>,
+    pub on_empty: Callback<MouseEvent>,
+    pub on_retention_change: Callback<u32>,
+}
+
+fn format_deleted_at(timestamp: f64) -> String {
+    js_sys::Date::new(&JsValue::from_f64(timestamp))
+        .to_locale_date_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+#[function_component(TrashPanel)]
+pub fn trash_panel(props: &TrashPanelProps) -> Html {
+    let on_retention_change = {
+        let on_retention_change = props.on_retention_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                if let Ok(days) = select.value().parse() {
+                    on_retention_change.emit(days);
+                }
+            }
+        })
+    };
+
+    let dataset_rows = props.trash.datasets.iter().enumerate().map(|(index, item)| {
+        let on_restore = props.on_restore_dataset.clone();
+        let on_purge = props.on_purge_dataset.clone();
+        let card_count = item.dataset.flashcards.len() + item.dataset.known_cards.len();
+        html! {
+            <tr>
+                <td>{ &item.dataset.name }</td>
+                <td>{ format!("{card_count} cards") }</td>
+                <td>{ format_deleted_at(item.deleted_at) }</td>
+                <td class="known-actions-cell">
+                    <div class="known-actions-group">
+                        <button class="btn btn-secondary" onclick={Callback::from(move |_| on_restore.emit(index))}>{"Restore"}</button>
+                        <button class="btn btn-danger" onclick={Callback::from(move |_| on_purge.emit(index))}>{"Delete Forever"}</button>
+                    </div>
+                </td>
+            </tr>
+        }
+    });
+
+    let card_rows = props.trash.cards.iter().enumerate().map(|(index, item)| {
+        let on_restore = props.on_restore_card.clone();
+        let on_purge = props.on_purge_card.clone();
+        let orphaned = !props.dataset_names.contains(&item.dataset);
+        let can_restore = !orphaned || !props.current_dataset.is_empty();
+        let title = match (orphaned, can_restore) {
+            (false, _) => format!("Restore into \"{}\"", item.dataset),
+            (true, true) => format!(
+                "\"{}\" no longer exists; restore into \"{}\"",
+                item.dataset, props.current_dataset
+            ),
+            (true, false) => format!(
+                "\"{}\" no longer exists; select a wordset to restore this card into",
+                item.dataset
+            ),
+        };
+        html! {
+            <tr>
+                <td>{ &item.card.word }</td>
+                <td>{ &item.card.translation }</td>
+                <td>{ &item.dataset }</td>
+                <td>{ format_deleted_at(item.deleted_at) }</td>
+                <td class="known-actions-cell">
+                    <div class="known-actions-group">
+                        <button
+                            class="btn btn-secondary"
+                            {title}
+                            disabled={!can_restore}
+                            onclick={Callback::from(move |_| on_restore.emit(index))}
+                        >
+                            {"Restore"}
+                        </button>
+                        <button class="btn btn-danger" onclick={Callback::from(move |_| on_purge.emit(index))}>{"Delete Forever"}</button>
+                    </div>
+                </td>
+            </tr>
+        }
+    });
+
+    let is_empty = props.trash.datasets.is_empty() && props.trash.cards.is_empty();
+
+    html! {
+        <div class="help-backdrop">
+            <section class="help-modal panel">
+                <div class="help-modal-header">
+                    <h2 class="panel-title help-modal-title">{"Trash"}</h2>
+                    <button class="btn btn-secondary btn-small help-close-btn" onclick={props.on_close.clone()}>
+                        {"✕ Close"}
+                    </button>
+                </div>
+
+                <div class="trash-settings">
+                    <label class="input-label" for="trash-retention">{"Keep deleted items for"}</label>
+                    <select id="trash-retention" class="text-input trash-retention" onchange={on_retention_change}>
+                        { for RETENTION_CHOICES.iter().map(|days| html! {
+                            <option value={days.to_string()} selected={*days == props.trash.retention_days}>
+                                { format!("{days} days") }
+                            </option>
+                        }) }
+                    </select>
+                </div>
+
+                if is_empty {
+                    <p class="muted-note">{"Trash is empty."}</p>
+                } else {
+                    if !props.trash.datasets.is_empty() {
+                        <h3 class="help-section-title">{"Wordsets"}</h3>
+                        <table class="known-table">
+                            <tr>
+                                <th>{"Name"}</th>
+                                <th>{"Size"}</th>
+                                <th>{"Deleted"}</th>
+                                <th>{"Action"}</th>
+                            </tr>
+                            { for dataset_rows }
+                        </table>
+                    }
+                    if !props.trash.cards.is_empty() {
+                        <h3 class="help-section-title trash-section-title">{"Cards"}</h3>
+                        <table class="known-table">
+                            <tr>
+                                <th>{"Word"}</th>
+                                <th>{"Translation"}</th>
+                                <th>{"Wordset"}</th>
+                                <th>{"Deleted"}</th>
+                                <th>{"Action"}</th>
+                            </tr>
+                            { for card_rows }
+                        </table>
+                    }
+                    <div class="panel-actions">
+                        <button class="btn btn-danger" onclick={props.on_empty.clone()}>{"Empty Trash"}</button>
+                    </div>
+                }
+            </section>
+        </div>
+    }
+}
@@ -1,34 +1,168 @@
-use csv::{IntoInnerError, Writer};
 use js_sys::{Array, Uint8Array};
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{Blob, Url};
 
-use crate::model::Flashcard;
+use crate::import::{field_label, RejectedRow, SourceRows};
+use crate::model::{ColumnField, Flashcard};
 
-pub fn parse_flashcards_from_csv(csv_data: &str) -> Vec<Flashcard> {
+pub const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+pub const QUOTES: [u8; 2] = [b'"', b'\''];
+const SAMPLE_LINES: usize = 20;
+
+/// Field delimiter and quote character of a delimited text file.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+        }
+    }
+}
+
+pub fn delimiter_label(delimiter: u8) -> &'static str {
+    match delimiter {
+        b',' => "Comma",
+        b'\t' => "Tab",
+        b';' => "Semicolon",
+        b'|' => "Pipe",
+        _ => "Other",
+    }
+}
+
+pub fn quote_label(quote: u8) -> &'static str {
+    match quote {
+        b'"' => "Double quote (\")",
+        b'\'' => "Single quote (')",
+        _ => "Other",
+    }
+}
+
+/// Number of `delimiter` bytes on each line that are not inside `quote`d text.
+fn delimiter_counts(sample: &[&str], delimiter: u8, quote: u8) -> Vec<usize> {
+    sample
+        .iter()
+        .map(|line| {
+            let mut quoted = false;
+            let mut count = 0;
+            for byte in line.bytes() {
+                if byte == quote {
+                    quoted = !quoted;
+                } else if byte == delimiter && !quoted {
+                    count += 1;
+                }
+            }
+            count
+        })
+        .collect()
+}
+
+/// Picks the quote character that wraps the most fields, preferring `"`.
+fn detect_quote(sample: &[&str]) -> u8 {
+    let wrapped = |quote: u8| {
+        sample
+            .iter()
+            .flat_map(|line| line.split(|c: char| c.is_ascii() && DELIMITERS.contains(&(c as u8))))
+            .map(|field| field.trim().as_bytes())
+            .filter(|field| {
+                field.len() >= 2 && field[0] == quote && field[field.len() - 1] == quote
+            })
+            .count()
+    };
+    QUOTES
+        .into_iter()
+        .max_by_key(|quote| (wrapped(*quote), *quote == b'"'))
+        .unwrap_or(b'"')
+}
+
+/// Guesses the delimiter and quote character from the first lines of a file.
+///
+/// The delimiter that splits the most sampled lines into the same number of fields
+/// wins; files where no candidate appears are treated as comma-separated.
+pub fn detect_dialect(text: &str) -> CsvDialect {
+    let sample: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SAMPLE_LINES)
+        .collect();
+    let quote = detect_quote(&sample);
+
+    let delimiter = DELIMITERS
+        .into_iter()
+        .filter_map(|delimiter| {
+            let counts = delimiter_counts(&sample, delimiter, quote);
+            let mode = counts
+                .iter()
+                .filter(|count| **count > 0)
+                .max_by_key(|count| counts.iter().filter(|other| other == count).count())?;
+            let consistent = counts.iter().filter(|count| *count == mode).count();
+            Some(((consistent, *mode), delimiter))
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, delimiter)| delimiter)
+        .unwrap_or(b',');
+
+    CsvDialect { delimiter, quote }
+}
+
+/// 1-based line of the first non-blank character at or after `byte`.
+fn line_at(csv_data: &str, byte: u64) -> usize {
+    let byte = usize::try_from(byte)
+        .unwrap_or(csv_data.len())
+        .min(csv_data.len());
+    let (before, after) = csv_data.as_bytes().split_at(byte);
+    let skipped_blank_lines = after
+        .iter()
+        .take_while(|b| matches!(b, b'\n' | b'\r'))
+        .filter(|b| **b == b'\n')
+        .count();
+    1 + before.iter().filter(|b| **b == b'\n').count() + skipped_blank_lines
+}
+
+pub fn read_csv_rows(csv_data: &str, dialect: CsvDialect) -> SourceRows {
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(false)
+        .flexible(true)
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
         .from_reader(csv_data.as_bytes());
 
-    reader
-        .records()
-        .filter_map(Result::ok)
-        .map(|record| Flashcard {
-            word: record.get(0).unwrap_or_default().to_string(),
-            pinyin: record.get(1).map(str::to_owned),
-            translation: record.get(2).unwrap_or_default().to_string(),
-            known: record
-                .get(3)
-                .map(|value| value.trim().eq_ignore_ascii_case("true"))
-                .unwrap_or(false),
-        })
-        .collect()
+    let mut source = SourceRows::default();
+    for result in reader.records() {
+        match result {
+            Ok(record) => {
+                let line = record
+                    .position()
+                    .map(|position| line_at(csv_data, position.byte()))
+                    .unwrap_or(source.rows.len() + 1);
+                source.line_numbers.push(line);
+                source.rows.push(record.iter().map(str::to_owned).collect());
+            }
+            Err(error) => source.errors.push(RejectedRow {
+                line: error
+                    .position()
+                    .map(|position| line_at(csv_data, position.byte()))
+                    .unwrap_or_default(),
+                reason: error.to_string(),
+            }),
+        }
+    }
+    source
 }
 
 pub fn export_flashcards_csv<'a>(
     cards: impl Iterator<Item = &'a Flashcard>,
-) -> Result<Vec<u8>, IntoInnerError<Writer<Vec<u8>>>> {
-    let mut writer = csv::Writer::from_writer(Vec::new());
+    dialect: CsvDialect,
+) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .from_writer(Vec::new());
 
     for card in cards {
         let record = [
@@ -40,7 +174,43 @@ pub fn export_flashcards_csv<'a>(
         let _ = writer.write_record(record);
     }
 
-    writer.into_inner()
+    writer
+        .into_inner()
+        .map_err(|error| error.into_error().into())
+}
+
+/// Text written for one field of a card in exported columns.
+pub fn field_text(card: &Flashcard, field: ColumnField) -> String {
+    match field {
+        ColumnField::Word => card.word.clone(),
+        ColumnField::Pinyin => card.pinyin.clone().unwrap_or_default(),
+        ColumnField::Translation => card.translation.clone(),
+        ColumnField::Known => card.known.to_string(),
+        ColumnField::Tags => card.tags.join(" "),
+        ColumnField::Ignore => String::new(),
+    }
+}
+
+/// Writes the chosen `columns` of each card after a header row naming them, so the
+/// file is mapped correctly when it is imported again.
+pub fn export_columns_csv<'a>(
+    cards: impl Iterator<Item = &'a Flashcard>,
+    columns: &[ColumnField],
+    dialect: CsvDialect,
+) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .from_writer(Vec::new());
+
+    writer.write_record(columns.iter().map(|field| field_label(*field)))?;
+    for card in cards {
+        writer.write_record(columns.iter().map(|field| field_text(card, *field)))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|error| error.into_error().into())
 }
 
 pub fn trigger_csv_download(bytes: &[u8], file_name: &str) -> Result<(), JsValue> {
@@ -67,11 +237,19 @@ pub fn trigger_csv_download(bytes: &[u8], file_name: &str) -> Result<(), JsValue
 
 #[cfg(test)]
 mod tests {
-    use super::{export_flashcards_csv, parse_flashcards_from_csv};
+    use super::{detect_dialect, export_flashcards_csv, read_csv_rows, CsvDialect};
+    use crate::import::{build_report, guess_mapping};
+    use crate::model::Flashcard;
+
+    /// The cards an import of `csv_data` creates with the detected dialect and guessed columns.
+    fn imported_cards(csv_data: &str) -> Vec<Flashcard> {
+        let source = read_csv_rows(csv_data, detect_dialect(csv_data));
+        build_report(&source, &guess_mapping(&source.rows)).cards()
+    }
 
     #[test]
     fn parses_known_column_from_csv() {
-        let cards = parse_flashcards_from_csv("阿姨,āyí,aunt,true\n啊,a,ah,false\n");
+        let cards = imported_cards("阿姨,āyí,aunt,true\n啊,a,ah,false\n");
 
         assert_eq!(cards.len(), 2);
         assert!(cards[0].known);
@@ -79,12 +257,74 @@ mod tests {
         assert_eq!(cards[0].translation, "aunt");
     }
 
+    #[test]
+    fn skips_detected_header_row() {
+        let cards = imported_cards("Pinyin,Word,Meaning\nāyí,阿姨,aunt\n");
+
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].word, "阿姨");
+        assert_eq!(cards[0].pinyin.as_deref(), Some("āyí"));
+    }
+
+    #[test]
+    fn records_source_line_numbers() {
+        let source = read_csv_rows(
+            "阿姨,āyí,aunt\n\n\"多行\nword\",duō,many\n啊,a,ah\n",
+            CsvDialect::default(),
+        );
+
+        assert_eq!(source.line_numbers, [1, 3, 5]);
+    }
+
     #[test]
     fn exports_known_state_to_csv() {
-        let cards = parse_flashcards_from_csv("阿姨,āyí,aunt,true\n");
-        let bytes = export_flashcards_csv(cards.iter()).expect("csv export should succeed");
+        let cards = imported_cards("阿姨,āyí,aunt,true\n");
+        let bytes = export_flashcards_csv(cards.iter(), CsvDialect::default())
+            .expect("csv export should succeed");
         let csv = String::from_utf8(bytes).expect("csv should be utf-8");
 
         assert!(csv.contains("阿姨,āyí,aunt,true"));
     }
+
+    #[test]
+    fn detects_semicolon_and_tab_delimiters() {
+        let semicolon = "Word;Pinyin;Meaning\n阿姨;āyí;aunt, auntie\n啊;a;\"ah; oh\"\n";
+        assert_eq!(detect_dialect(semicolon).delimiter, b';');
+
+        let cards = imported_cards(semicolon);
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].translation, "aunt, auntie");
+        assert_eq!(cards[1].translation, "ah; oh");
+
+        let tab = "阿姨\tāyí\taunt, auntie\n啊\ta\tah\n";
+        assert_eq!(detect_dialect(tab).delimiter, b'\t');
+    }
+
+    #[test]
+    fn detects_single_quotes() {
+        let dialect = detect_dialect("'阿姨','āyí','aunt, auntie'\n'啊','a','ah'\n");
+
+        assert_eq!(
+            dialect,
+            CsvDialect {
+                delimiter: b',',
+                quote: b'\''
+            }
+        );
+        let source = read_csv_rows("'阿姨','āyí','aunt, auntie'\n", dialect);
+        assert_eq!(source.rows[0][2], "aunt, auntie");
+    }
+
+    #[test]
+    fn exports_tab_separated_values() {
+        let cards = imported_cards("阿姨,āyí,aunt,true\n");
+        let dialect = CsvDialect {
+            delimiter: b'\t',
+            ..CsvDialect::default()
+        };
+        let bytes =
+            export_flashcards_csv(cards.iter(), dialect).expect("tsv export should succeed");
+
+        assert_eq!(String::from_utf8(bytes).unwrap(), "阿姨\tāyí\taunt\ttrue\n");
+    }
 }
@@ -0,0 +1,116 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, BIG5, GB18030, GBK, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Encodings offered for imports, in the order they are listed.
+pub const ENCODINGS: [&Encoding; 6] = [UTF_8, GBK, GB18030, BIG5, UTF_16LE, UTF_16BE];
+
+pub fn encoding_label(encoding: &'static Encoding) -> &'static str {
+    if encoding == UTF_8 {
+        "UTF-8"
+    } else if encoding == GBK {
+        "GBK (Simplified Chinese)"
+    } else if encoding == GB18030 {
+        "GB18030 (Simplified Chinese)"
+    } else if encoding == BIG5 {
+        "Big5 (Traditional Chinese)"
+    } else if encoding == UTF_16LE {
+        "UTF-16 LE"
+    } else if encoding == UTF_16BE {
+        "UTF-16 BE"
+    } else {
+        encoding.name()
+    }
+}
+
+/// Recognises BOM-less UTF-16 by the zero bytes that ASCII characters leave in
+/// every other position.
+fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(1024) & !1];
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let zeros_at = |offset: usize| {
+        sample
+            .iter()
+            .skip(offset)
+            .step_by(2)
+            .filter(|b| **b == 0)
+            .count()
+    };
+    if zeros_at(1) * 2 > pairs && zeros_at(0) == 0 {
+        Some(UTF_16LE)
+    } else if zeros_at(0) * 2 > pairs && zeros_at(1) == 0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Guesses the encoding of an import file: a byte order mark wins, then UTF-16
+/// and UTF-8 are checked directly, and anything else is left to a statistical
+/// detector that tells GBK from Big5.
+pub fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if let Some(encoding) = detect_utf16(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// Decodes `bytes` with `encoding`, dropping a byte order mark that matches it.
+/// The flag is set when some bytes were invalid and replaced with U+FFFD.
+pub fn decode(bytes: &[u8], encoding: &'static Encoding) -> (String, bool) {
+    let (text, had_errors) = encoding.decode_with_bom_removal(bytes);
+    (text.into_owned(), had_errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, detect_encoding};
+    use encoding_rs::{BIG5, GBK, UTF_16LE, UTF_8};
+
+    #[test]
+    fn detects_and_strips_byte_order_marks() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("你好,hello".encode_utf16().flat_map(u16::to_le_bytes));
+
+        let encoding = detect_encoding(&bytes);
+        assert!(encoding == UTF_16LE);
+        assert_eq!(decode(&bytes, encoding), ("你好,hello".to_string(), false));
+
+        let utf8 = "\u{feff}你好,hello".as_bytes();
+        assert!(detect_encoding(utf8) == UTF_8);
+        assert_eq!(decode(utf8, UTF_8).0, "你好,hello");
+    }
+
+    #[test]
+    fn tells_gbk_from_big5() {
+        let simplified = "你好,hello\n学习,study\n电脑,computer\n我们的朋友,our friend\n";
+        let (bytes, _, _) = GBK.encode(simplified);
+        assert!(detect_encoding(&bytes) == GBK);
+        assert_eq!(decode(&bytes, GBK).0, simplified);
+
+        let traditional = "你好,hello\n學習,study\n電腦,computer\n我們的朋友,our friend\n";
+        let (bytes, _, _) = BIG5.encode(traditional);
+        assert!(detect_encoding(&bytes) == BIG5);
+        assert_eq!(decode(&bytes, BIG5).0, traditional);
+    }
+
+    #[test]
+    fn detects_utf16_without_bom() {
+        let bytes: Vec<u8> = "word,translation\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+
+        assert!(detect_encoding(&bytes) == UTF_16LE);
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::BTreeSet;
+
+use crate::anki::export_anki_text;
+use crate::bundle::export_bundle;
+use crate::csv_io::{delimiter_label, export_columns_csv, export_flashcards_csv, CsvDialect};
+use crate::json_io::export_wordset_json;
+use crate::markdown::export_markdown;
+use crate::model::{ColumnField, Dataset, Flashcard};
+use crate::pleco::export_pleco;
+use crate::quizlet::{export_quizlet, QuizletSeparators};
+use crate::xlsx::export_flashcards_xlsx;
+
+/// File formats a wordset can be exported to.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// Our own four-column layout, separated by the given delimiter.
+    Delimited(u8),
+    /// Tab-separated notes with the header lines Anki's text importer understands.
+    AnkiText,
+    /// Pleco's flashcard format: a `//Category` line, then word, numbered pinyin and definition.
+    Pleco,
+    /// Quizlet's Import box text: `word (pinyin)` and the definition, with the chosen separators.
+    Quizlet,
+    /// A Markdown table with the known state as a task checkbox.
+    Markdown,
+    /// An Excel workbook with formatted headers and a progress summary sheet.
+    Excel,
+    /// The lossless, versioned wordset JSON.
+    Json,
+    /// A zip holding the wordset JSON and its media, described in [`crate::bundle`].
+    Bundle,
+}
+
+impl Default for ExportFormat {
+    fn default() -> Self {
+        ExportFormat::Delimited(b',')
+    }
+}
+
+pub const EXPORT_FORMATS: [ExportFormat; 11] = [
+    ExportFormat::Delimited(b','),
+    ExportFormat::Delimited(b'\t'),
+    ExportFormat::Delimited(b';'),
+    ExportFormat::Delimited(b'|'),
+    ExportFormat::Excel,
+    ExportFormat::Markdown,
+    ExportFormat::AnkiText,
+    ExportFormat::Pleco,
+    ExportFormat::Quizlet,
+    ExportFormat::Json,
+    ExportFormat::Bundle,
+];
+
+pub fn export_format_label(format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Delimited(b',') => "CSV".to_string(),
+        ExportFormat::Delimited(b'\t') => "Tab (TSV)".to_string(),
+        ExportFormat::Delimited(delimiter) => format!("{} separated", delimiter_label(delimiter)),
+        ExportFormat::AnkiText => "Anki (text import)".to_string(),
+        ExportFormat::Pleco => "Pleco flashcards".to_string(),
+        ExportFormat::Quizlet => "Quizlet (import text)".to_string(),
+        ExportFormat::Excel => "Excel workbook".to_string(),
+        ExportFormat::Markdown => "Markdown table".to_string(),
+        ExportFormat::Json => "Wordset JSON (lossless)".to_string(),
+        ExportFormat::Bundle => "Wordset bundle (.zip)".to_string(),
+    }
+}
+
+/// Card fields that can be written as columns, in the order they are written.
+pub const EXPORT_FIELDS: [ColumnField; 5] = [
+    ColumnField::Word,
+    ColumnField::Pinyin,
+    ColumnField::Translation,
+    ColumnField::Known,
+    ColumnField::Tags,
+];
+/// Columns of the headerless CSV layout exported before columns could be chosen.
+const CLASSIC_COLUMNS: [ColumnField; 4] = [
+    ColumnField::Word,
+    ColumnField::Pinyin,
+    ColumnField::Translation,
+    ColumnField::Known,
+];
+
+/// Which cards of the wordset an export includes.
+#[derive(Clone, PartialEq, Default)]
+pub enum ExportScope {
+    #[default]
+    All,
+    Unknown,
+    Known,
+    /// Cards picked in the export dialog, by position in unknown-then-known order.
+    Selected(BTreeSet<usize>),
+    Tag(String),
+}
+
+/// Everything chosen in the export dialog.
+#[derive(Clone, PartialEq)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub scope: ExportScope,
+    /// Columns written by the delimited and Excel formats; the others have a fixed layout.
+    pub columns: Vec<ColumnField>,
+    /// Separators written by the Quizlet format.
+    pub quizlet: QuizletSeparators,
+    /// File name without its extension.
+    pub file_stem: String,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            scope: ExportScope::default(),
+            columns: CLASSIC_COLUMNS.to_vec(),
+            quizlet: QuizletSeparators::default(),
+            file_stem: String::new(),
+        }
+    }
+}
+
+impl ExportFormat {
+    /// Whether the columns of this format can be chosen.
+    pub fn has_columns(self) -> bool {
+        matches!(self, ExportFormat::Delimited(_) | ExportFormat::Excel)
+    }
+}
+
+/// File name suggested for exporting the wordset `name` on `date` (`YYYY-MM-DD`),
+/// with characters that file systems reject replaced.
+pub fn default_file_stem(name: &str, date: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if "/\\:*?\"<>|".contains(c) { '-' } else { c })
+        .collect();
+    format!("{} {date}", name.trim()).trim().to_string()
+}
+
+/// Distinct tags used by the cards of `dataset`, sorted.
+pub fn dataset_tags(dataset: &Dataset) -> Vec<String> {
+    dataset
+        .flashcards
+        .iter()
+        .chain(&dataset.known_cards)
+        .flat_map(|card| card.tags.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// The part of `dataset` covered by `scope`, keeping study order and progress.
+pub fn scoped_dataset(dataset: &Dataset, scope: &ExportScope) -> Dataset {
+    let unknown_count = dataset.flashcards.len();
+    let keep = |index: usize, card: &Flashcard| match scope {
+        ExportScope::All => true,
+        ExportScope::Unknown => index < unknown_count,
+        ExportScope::Known => index >= unknown_count,
+        ExportScope::Selected(selected) => selected.contains(&index),
+        ExportScope::Tag(tag) => card.tags.contains(tag),
+    };
+    let filter = |cards: &[Flashcard], offset: usize| {
+        cards
+            .iter()
+            .enumerate()
+            .filter(|(index, card)| keep(offset + index, card))
+            .map(|(_, card)| card.clone())
+            .collect()
+    };
+    Dataset {
+        name: dataset.name.clone(),
+        flashcards: filter(&dataset.flashcards, 0),
+        known_cards: filter(&dataset.known_cards, unknown_count),
+        column_mapping: dataset.column_mapping.clone(),
+    }
+}
+
+/// File name for `stem` with the extension of `format`.
+pub fn export_file_name(stem: &str, format: ExportFormat) -> String {
+    let extension = match format {
+        ExportFormat::Delimited(b'\t') => "tsv",
+        ExportFormat::Delimited(_) => "csv",
+        ExportFormat::AnkiText | ExportFormat::Pleco | ExportFormat::Quizlet => "txt",
+        ExportFormat::Excel => "xlsx",
+        ExportFormat::Markdown => "md",
+        ExportFormat::Json => "json",
+        ExportFormat::Bundle => "zip",
+    };
+    format!("{stem}.{extension}")
+}
+
+/// Serialises the cards of `dataset` chosen by `options` and returns the bytes
+/// together with the file name to download them as. `exported_at` is the ISO 8601
+/// time recorded in JSON exports.
+pub fn export_wordset(
+    options: &ExportOptions,
+    dataset: &Dataset,
+    exported_at: &str,
+) -> Result<(Vec<u8>, String), String> {
+    let dataset = scoped_dataset(dataset, &options.scope);
+    let name = dataset.name.as_str();
+    let cards: Vec<Flashcard> = dataset
+        .flashcards
+        .iter()
+        .chain(&dataset.known_cards)
+        .cloned()
+        .collect();
+    if cards.is_empty() {
+        return Err("no cards match the chosen scope".to_string());
+    }
+    if options.format.has_columns() && options.columns.is_empty() {
+        return Err("choose at least one column".to_string());
+    }
+    let cards = cards.as_slice();
+    let bytes = match options.format {
+        ExportFormat::Delimited(delimiter) => {
+            let dialect = CsvDialect {
+                delimiter,
+                ..CsvDialect::default()
+            };
+            if options.columns == CLASSIC_COLUMNS {
+                export_flashcards_csv(cards.iter(), dialect)
+            } else {
+                export_columns_csv(cards.iter(), &options.columns, dialect)
+            }
+            .map_err(|error| error.to_string())?
+        }
+        ExportFormat::AnkiText => {
+            export_anki_text(name, cards).map_err(|error| error.to_string())?
+        }
+        ExportFormat::Pleco => export_pleco(name, cards),
+        ExportFormat::Quizlet => export_quizlet(cards, &options.quizlet),
+        ExportFormat::Markdown => export_markdown(name, cards),
+        ExportFormat::Excel => export_flashcards_xlsx(name, cards, &options.columns)
+            .map_err(|error| error.to_string())?,
+        ExportFormat::Json => {
+            export_wordset_json(&dataset, exported_at).map_err(|error| error.to_string())?
+        }
+        ExportFormat::Bundle => export_bundle(&dataset, exported_at)?,
+    };
+    let stem = match options.file_stem.trim() {
+        "" => default_file_stem(name, exported_at.get(..10).unwrap_or_default()),
+        stem => stem.to_string(),
+    };
+    Ok((bytes, export_file_name(&stem, options.format)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{
+        dataset_tags, default_file_stem, export_wordset, scoped_dataset, ExportFormat,
+        ExportOptions, ExportScope,
+    };
+    use crate::model::{ColumnField, Dataset, Flashcard};
+
+    fn card(word: &str, known: bool, tags: &[&str]) -> Flashcard {
+        Flashcard {
+            word: word.to_string(),
+            pinyin: None,
+            translation: format!("{word} meaning"),
+            known,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    fn dataset() -> Dataset {
+        Dataset {
+            name: "HSK 1".to_string(),
+            flashcards: vec![card("一", false, &["number"]), card("人", false, &[])],
+            known_cards: vec![card("二", true, &["number"])],
+            column_mapping: None,
+        }
+    }
+
+    fn words(dataset: &Dataset) -> Vec<&str> {
+        dataset
+            .flashcards
+            .iter()
+            .chain(&dataset.known_cards)
+            .map(|card| card.word.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn scopes_pick_cards_by_status_selection_or_tag() {
+        let dataset = dataset();
+        let scoped = |scope: ExportScope| scoped_dataset(&dataset, &scope);
+
+        assert_eq!(words(&scoped(ExportScope::All)), ["一", "人", "二"]);
+        assert_eq!(words(&scoped(ExportScope::Unknown)), ["一", "人"]);
+        assert_eq!(words(&scoped(ExportScope::Known)), ["二"]);
+        assert_eq!(
+            words(&scoped(ExportScope::Selected(BTreeSet::from([1, 2])))),
+            ["人", "二"]
+        );
+        assert_eq!(
+            words(&scoped(ExportScope::Tag("number".to_string()))),
+            ["一", "二"]
+        );
+        assert_eq!(dataset_tags(&dataset), ["number"]);
+    }
+
+    #[test]
+    fn writes_chosen_columns_under_a_header() {
+        let options = ExportOptions {
+            scope: ExportScope::Known,
+            columns: vec![ColumnField::Word, ColumnField::Tags],
+            file_stem: "numbers".to_string(),
+            ..ExportOptions::default()
+        };
+        let (bytes, file_name) = export_wordset(&options, &dataset(), "").unwrap();
+
+        assert_eq!(String::from_utf8(bytes).unwrap(), "Word,Tags\n二,number\n");
+        assert_eq!(file_name, "numbers.csv");
+
+        let classic = export_wordset(&ExportOptions::default(), &dataset(), "").unwrap();
+        assert!(String::from_utf8(classic.0)
+            .unwrap()
+            .starts_with("一,,一 meaning,false\n"));
+
+        let empty = ExportOptions {
+            scope: ExportScope::Tag("food".to_string()),
+            ..ExportOptions::default()
+        };
+        assert!(export_wordset(&empty, &dataset(), "").is_err());
+    }
+
+    #[test]
+    fn file_names_default_to_wordset_and_date() {
+        assert_eq!(
+            default_file_stem("HSK / Unit 1", "2026-10-18"),
+            "HSK - Unit 1 2026-10-18"
+        );
+
+        let options = ExportOptions {
+            format: ExportFormat::Excel,
+            ..ExportOptions::default()
+        };
+        let (_, file_name) =
+            export_wordset(&options, &dataset(), "2026-10-18T09:30:00.000Z").unwrap();
+        assert_eq!(file_name, "HSK 1 2026-10-18.xlsx");
+    }
+}
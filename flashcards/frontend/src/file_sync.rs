@@ -0,0 +1,259 @@
+//! Syncing a wordset with an updated copy of a list kept outside the app, matching cards by word.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{ColumnField, Flashcard};
+
+#[derive(Clone, PartialEq)]
+pub struct ChangedCard {
+    pub before: Flashcard,
+    pub after: Flashcard,
+}
+
+impl ChangedCard {
+    pub fn describe(&self) -> String {
+        let (before, after) = (&self.before, &self.after);
+        let mut changes = Vec::new();
+        if before.pinyin != after.pinyin {
+            changes.push(format!(
+                "pinyin \"{}\" → \"{}\"",
+                before.pinyin.as_deref().unwrap_or_default(),
+                after.pinyin.as_deref().unwrap_or_default()
+            ));
+        }
+        if before.translation != after.translation {
+            changes.push(format!(
+                "translation \"{}\" → \"{}\"",
+                before.translation, after.translation
+            ));
+        }
+        if before.tags != after.tags {
+            changes.push(format!(
+                "tags \"{}\" → \"{}\"",
+                before.tags.join(" "),
+                after.tags.join(" ")
+            ));
+        }
+        if before.known != after.known {
+            changes.push(
+                if after.known {
+                    "now known"
+                } else {
+                    "no longer known"
+                }
+                .to_string(),
+            );
+        }
+        changes.join(", ")
+    }
+}
+
+/// What applying a file to a wordset would change.
+#[derive(Clone, PartialEq, Default)]
+pub struct SyncDiff {
+    pub added: Vec<Flashcard>,
+    /// Cards for words the file no longer has, and extra cards for repeated words.
+    pub removed: Vec<Flashcard>,
+    pub changed: Vec<ChangedCard>,
+    pub unchanged: usize,
+}
+
+impl SyncDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} added, {} changed, {} removed, {} unchanged",
+            self.added.len(),
+            self.changed.len(),
+            self.removed.len(),
+            self.unchanged
+        )
+    }
+}
+
+fn key(word: &str) -> &str {
+    word.trim()
+}
+
+/// Compares the cards of a wordset with those of a file whose columns hold `fields`.
+/// Only those fields are taken from the file, and its first row counts for a repeated word.
+pub fn diff_with_file(
+    current: &[Flashcard],
+    incoming: &[Flashcard],
+    fields: &[ColumnField],
+) -> SyncDiff {
+    let mut diff = SyncDiff::default();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for (index, card) in current.iter().enumerate().rev() {
+        positions.insert(key(&card.word), index);
+    }
+    let mut matched = vec![false; current.len()];
+    let mut seen = HashSet::new();
+
+    for card in incoming {
+        if !seen.insert(key(&card.word)) {
+            continue;
+        }
+        let Some(&index) = positions.get(key(&card.word)) else {
+            diff.added.push(card.clone());
+            continue;
+        };
+        matched[index] = true;
+        let before = &current[index];
+        let mut after = before.clone();
+        for field in fields {
+            match field {
+                ColumnField::Pinyin => after.pinyin = card.pinyin.clone(),
+                ColumnField::Translation => after.translation = card.translation.clone(),
+                ColumnField::Tags => after.tags = card.tags.clone(),
+                ColumnField::Known => after.known = card.known,
+                _ => {}
+            }
+        }
+        if after == *before {
+            diff.unchanged += 1;
+        } else {
+            diff.changed.push(ChangedCard {
+                before: before.clone(),
+                after,
+            });
+        }
+    }
+
+    diff.removed = current
+        .iter()
+        .zip(&matched)
+        .filter(|(_, matched)| !**matched)
+        .map(|(card, _)| card.clone())
+        .collect();
+    diff
+}
+
+/// Applies `diff` to the wordset it was computed for. Cards whose known state changes
+/// move to the end of the other list, like cards marked by hand.
+pub fn apply_sync(unknown: &mut Vec<Flashcard>, known: &mut Vec<Flashcard>, diff: &SyncDiff) {
+    for change in &diff.changed {
+        let list = if change.before.known {
+            &mut *known
+        } else {
+            &mut *unknown
+        };
+        let Some(index) = list.iter().position(|card| *card == change.before) else {
+            continue;
+        };
+        if change.before.known == change.after.known {
+            list[index] = change.after.clone();
+        } else {
+            list.remove(index);
+            if change.after.known {
+                known.push(change.after.clone());
+            } else {
+                unknown.push(change.after.clone());
+            }
+        }
+    }
+    for card in &diff.removed {
+        let list = if card.known {
+            &mut *known
+        } else {
+            &mut *unknown
+        };
+        if let Some(index) = list.iter().position(|existing| existing == card) {
+            list.remove(index);
+        }
+    }
+    for card in &diff.added {
+        if card.known {
+            known.push(card.clone());
+        } else {
+            unknown.push(card.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_sync, diff_with_file};
+    use crate::model::{ColumnField, Flashcard};
+
+    fn card(word: &str, translation: &str, known: bool) -> Flashcard {
+        Flashcard {
+            word: word.to_string(),
+            pinyin: None,
+            translation: translation.to_string(),
+            known,
+            tags: Vec::new(),
+        }
+    }
+
+    const FIELDS: [ColumnField; 2] = [ColumnField::Word, ColumnField::Translation];
+
+    #[test]
+    fn finds_added_removed_and_changed_words() {
+        let current = [
+            card("你好", "hello", false),
+            card("谢谢", "thanks", true),
+            card("再见", "bye", false),
+        ];
+        let incoming = [
+            card("谢谢", "thank you", false),
+            card(" 你好 ", "hello", false),
+            card("朋友", "friend", false),
+            card("朋友", "pal", false),
+        ];
+        let diff = diff_with_file(&current, &incoming, &FIELDS);
+
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].translation, "friend");
+        assert!(diff.removed == [card("再见", "bye", false)]);
+        assert_eq!(diff.changed.len(), 1);
+        // Without a Known column the file cannot reset progress.
+        assert!(diff.changed[0].after == card("谢谢", "thank you", true));
+        assert_eq!(
+            diff.changed[0].describe(),
+            "translation \"thanks\" → \"thank you\""
+        );
+        assert_eq!(
+            diff.describe(),
+            "1 added, 1 changed, 1 removed, 1 unchanged"
+        );
+    }
+
+    #[test]
+    fn applies_changes_keeping_progress_and_order() {
+        let mut unknown = vec![card("你好", "hi", false), card("再见", "bye", false)];
+        let mut known = vec![card("谢谢", "thanks", true)];
+        let all: Vec<_> = unknown.iter().chain(&known).cloned().collect();
+        let incoming = [
+            card("谢谢", "thanks", true),
+            card("你好", "hello", false),
+            card("朋友", "friend", false),
+        ];
+        let diff = diff_with_file(&all, &incoming, &FIELDS);
+        apply_sync(&mut unknown, &mut known, &diff);
+
+        assert!(unknown == [card("你好", "hello", false), card("朋友", "friend", false)]);
+        assert!(known == [card("谢谢", "thanks", true)]);
+        assert!(diff_with_file(&[unknown, known].concat(), &incoming, &FIELDS).is_empty());
+    }
+
+    #[test]
+    fn takes_progress_from_a_known_column() {
+        let mut unknown = vec![card("你好", "hello", false), card("你好", "hello", false)];
+        let mut known = vec![card("谢谢", "thanks", true)];
+        let all: Vec<_> = unknown.iter().chain(&known).cloned().collect();
+        let incoming = [card("你好", "hello", true), card("谢谢", "thanks", false)];
+        let fields = [ColumnField::Word, ColumnField::Known];
+        let diff = diff_with_file(&all, &incoming, &fields);
+
+        // The second copy of the repeated word is not in the file.
+        assert_eq!(diff.removed.len(), 1);
+        apply_sync(&mut unknown, &mut known, &diff);
+        assert!(unknown == [card("谢谢", "thanks", false)]);
+        assert!(known == [card("你好", "hello", true)]);
+    }
+}
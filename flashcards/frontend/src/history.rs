@@ -0,0 +1,123 @@
+use crate::model::{Dataset, Flashcard, Trash};
+
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Clone, PartialEq)]
+pub struct Snapshot {
+    pub datasets: Vec<Dataset>,
+    pub current_dataset: String,
+    pub flashcards: Vec<Flashcard>,
+    pub known_cards: Vec<Flashcard>,
+    pub current_index: usize,
+    pub trash: Trash,
+}
+
+#[derive(Clone, PartialEq)]
+struct HistoryEntry {
+    label: String,
+    snapshot: Snapshot,
+}
+
+/// Bounded undo/redo stacks of whole-app snapshots, newest entry last.
+#[derive(Clone, Default, PartialEq)]
+pub struct History {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Stores the state from before an operation; any redo entries are discarded.
+    pub fn record(&mut self, label: &str, snapshot: Snapshot) {
+        self.undo.push(HistoryEntry {
+            label: label.to_string(),
+            snapshot,
+        });
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let entry = self.undo.pop()?;
+        self.redo.push(HistoryEntry {
+            label: entry.label,
+            snapshot: current,
+        });
+        Some(entry.snapshot)
+    }
+
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let entry = self.redo.pop()?;
+        self.undo.push(HistoryEntry {
+            label: entry.label,
+            snapshot: current,
+        });
+        Some(entry.snapshot)
+    }
+
+    /// Changes the trash of every stored snapshot, so that items purged from the
+    /// trash cannot come back through undo or redo.
+    pub fn edit_trash(&mut self, edit: impl Fn(&mut Trash)) {
+        for entry in self.undo.iter_mut().chain(&mut self.redo) {
+            edit(&mut entry.snapshot.trash);
+        }
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|entry| entry.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|entry| entry.label.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{History, Snapshot};
+    use crate::model::Trash;
+
+    fn snapshot(current_index: usize) -> Snapshot {
+        Snapshot {
+            datasets: Vec::new(),
+            current_dataset: String::new(),
+            flashcards: Vec::new(),
+            known_cards: Vec::new(),
+            current_index,
+            trash: Trash::default(),
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_swap_snapshots() {
+        let mut history = History::default();
+        history.record("Delete card", snapshot(1));
+
+        let restored = history.undo(snapshot(2)).expect("undo entry");
+        assert_eq!(restored.current_index, 1);
+        assert_eq!(history.redo_label(), Some("Delete card"));
+
+        let reapplied = history.redo(snapshot(1)).expect("redo entry");
+        assert_eq!(reapplied.current_index, 2);
+        assert_eq!(history.undo_label(), Some("Delete card"));
+    }
+
+    #[test]
+    fn recording_clears_redo_and_caps_length() {
+        let mut history = History::default();
+        history.record("Add card", snapshot(0));
+        history.undo(snapshot(1));
+        history.record("Shuffle", snapshot(0));
+        assert_eq!(history.redo_label(), None);
+
+        for index in 0..60 {
+            history.record("Mark known", snapshot(index));
+        }
+        let mut count = 0;
+        while history.undo(snapshot(0)).is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 50);
+    }
+}
@@ -0,0 +1,1018 @@
+use encoding_rs::Encoding;
+
+use crate::anki::read_apkg;
+use crate::bundle::read_bundle;
+use crate::csv_io::{detect_dialect, read_csv_rows, CsvDialect};
+use crate::encoding::{decode, detect_encoding};
+use crate::json_io::read_wordset_json;
+use crate::markdown::{looks_like_markdown, read_markdown};
+use crate::model::{ColumnField, ColumnMapping, Dataset, Flashcard};
+use crate::pleco::{looks_like_pleco, pleco_wordsets, read_pleco};
+use crate::quizlet::{looks_like_quizlet, read_quizlet, QuizletSeparators};
+use crate::xlsx::{read_xlsx, Sheet};
+
+/// Column order assumed for files without a header row.
+const DEFAULT_COLUMNS: [ColumnField; 4] = [
+    ColumnField::Word,
+    ColumnField::Pinyin,
+    ColumnField::Translation,
+    ColumnField::Known,
+];
+
+pub const FIELD_CHOICES: [ColumnField; 6] = [
+    ColumnField::Word,
+    ColumnField::Pinyin,
+    ColumnField::Translation,
+    ColumnField::Known,
+    ColumnField::Tags,
+    ColumnField::Ignore,
+];
+
+const TONE_VOWELS: &str = "āáǎàēéěèīíǐìōóǒòūúǔùǖǘǚǜüĀÁǍÀĒÉĚÈĪÍǏÌŌÓǑÒŪÚǓÙǕǗǙǛÜ";
+
+#[derive(Clone, PartialEq)]
+pub struct RejectedRow {
+    /// 1-based line in the source file.
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Cells read from an import file, with the source line of each row and the
+/// records that could not be read at all.
+#[derive(Clone, PartialEq, Default)]
+pub struct SourceRows {
+    pub rows: Vec<Vec<String>>,
+    pub line_numbers: Vec<usize>,
+    pub errors: Vec<RejectedRow>,
+}
+
+/// How imported cards are combined with the cards already in a wordset.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum MergeStrategy {
+    /// Leave existing cards alone and only add words that are not there yet.
+    #[default]
+    SkipDuplicates,
+    /// Overwrite pinyin and translation of existing words, keeping their progress.
+    UpdateExisting,
+    /// Add every imported card, even if the word already exists.
+    AppendAll,
+    /// Make the wordset match the file: add and remove words and update changed
+    /// ones, keeping progress. Applied with [`crate::file_sync`], not [`merge_cards`].
+    SyncWithFile,
+}
+
+pub const MERGE_STRATEGIES: [MergeStrategy; 4] = [
+    MergeStrategy::SkipDuplicates,
+    MergeStrategy::UpdateExisting,
+    MergeStrategy::AppendAll,
+    MergeStrategy::SyncWithFile,
+];
+
+/// A delimited text file, kept in its raw and decoded forms so that it can be
+/// read again with another encoding or dialect.
+#[derive(Clone, PartialEq)]
+pub struct DelimitedText {
+    pub bytes: Vec<u8>,
+    pub encoding: &'static Encoding,
+    /// Whether some bytes were not valid in `encoding` and were replaced.
+    pub decoding_errors: bool,
+    pub text: String,
+    pub dialect: CsvDialect,
+}
+
+impl DelimitedText {
+    pub fn new(bytes: Vec<u8>, encoding: &'static Encoding) -> Self {
+        let (text, decoding_errors) = decode(&bytes, encoding);
+        let dialect = detect_dialect(&text);
+        Self {
+            bytes,
+            encoding,
+            decoding_errors,
+            text,
+            dialect,
+        }
+    }
+
+    /// Decodes the bytes again with `encoding` and re-detects the dialect.
+    fn set_encoding(&mut self, encoding: &'static Encoding) {
+        *self = Self::new(std::mem::take(&mut self.bytes), encoding);
+    }
+}
+
+/// The kind of file an import was read from, which decides the options the preview offers.
+#[derive(Clone, PartialEq)]
+pub enum ImportFormat {
+    Delimited(DelimitedText),
+    /// An Excel workbook, read one worksheet at a time.
+    Spreadsheet {
+        sheets: Vec<Sheet>,
+        selected: usize,
+    },
+    /// An Anki deck, imported into a new wordset named after the deck.
+    Anki {
+        deck_name: String,
+    },
+    /// Pleco flashcards, imported into one new wordset with categories as tags,
+    /// or into one new wordset per category.
+    Pleco {
+        text: DelimitedText,
+        file_stem: String,
+        split_categories: bool,
+    },
+    /// A table or dash-separated bullet list from Markdown notes.
+    Markdown {
+        text: DelimitedText,
+    },
+    /// A Quizlet export, split into cards with the separators chosen on export.
+    Quizlet {
+        text: DelimitedText,
+        separators: QuizletSeparators,
+    },
+}
+
+/// Where the cards of a delimited text import go.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum ImportTarget {
+    /// Merge into the selected wordset.
+    #[default]
+    CurrentWordset,
+    /// Create a wordset named after the file.
+    NewWordset,
+}
+
+/// Rows read from an import file, waiting for the user to confirm the column mapping.
+#[derive(Clone, PartialEq)]
+pub struct PendingImport {
+    pub file_name: String,
+    pub format: ImportFormat,
+    pub source: SourceRows,
+    pub mapping: ColumnMapping,
+    pub strategy: MergeStrategy,
+    pub target: ImportTarget,
+}
+
+/// What a chosen, dropped or pasted file turned out to hold.
+pub enum ImportedFile {
+    /// Rows to preview and map to card fields.
+    Rows(PendingImport),
+    /// A complete wordset from a JSON export, added as it is.
+    Wordset(Dataset),
+}
+
+/// Reads a wordset JSON export or bundle, or any file [`PendingImport::from_file`] understands.
+pub fn read_import_file(
+    file_name: String,
+    bytes: Vec<u8>,
+    remembered: Option<&ColumnMapping>,
+) -> Result<ImportedFile, String> {
+    if file_name.to_lowercase().ends_with(".json") {
+        let text = String::from_utf8(bytes)
+            .map_err(|_| "Wordset JSON files must be UTF-8 encoded.".to_string())?;
+        return read_wordset_json(&text).map(ImportedFile::Wordset);
+    }
+    if file_name.to_lowercase().ends_with(".zip") {
+        return read_bundle(&bytes).map(ImportedFile::Wordset);
+    }
+    PendingImport::from_file(file_name, bytes, remembered).map(ImportedFile::Rows)
+}
+
+/// `name` without its extension.
+pub fn file_stem(name: &str) -> &str {
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+impl PendingImport {
+    /// Reads an Anki deck, an Excel workbook or a text file, telling them apart by extension.
+    pub fn from_file(
+        file_name: String,
+        bytes: Vec<u8>,
+        remembered: Option<&ColumnMapping>,
+    ) -> Result<Self, String> {
+        let lower = file_name.to_lowercase();
+        if lower.ends_with(".xlsx") {
+            return Self::spreadsheet(file_name, &bytes, remembered);
+        }
+        if !lower.ends_with(".apkg") {
+            return Ok(Self::text_file(file_name, bytes, remembered));
+        }
+        let deck = read_apkg(&bytes, file_stem(&file_name))?;
+        Ok(Self {
+            format: ImportFormat::Anki {
+                deck_name: deck.name,
+            },
+            file_name,
+            source: deck.source,
+            mapping: deck.mapping,
+            strategy: MergeStrategy::default(),
+            target: ImportTarget::NewWordset,
+        })
+    }
+
+    /// Reads an Excel workbook, starting with its first worksheet that has any rows.
+    pub fn spreadsheet(
+        file_name: String,
+        bytes: &[u8],
+        remembered: Option<&ColumnMapping>,
+    ) -> Result<Self, String> {
+        let sheets = read_xlsx(bytes)?;
+        let selected = sheets
+            .iter()
+            .position(|sheet| !sheet.rows.rows.is_empty())
+            .unwrap_or_default();
+        let source = sheets[selected].rows.clone();
+        let mapping = initial_mapping(&source.rows, remembered);
+        Ok(Self {
+            file_name,
+            format: ImportFormat::Spreadsheet { sheets, selected },
+            source,
+            mapping,
+            strategy: MergeStrategy::default(),
+            target: ImportTarget::default(),
+        })
+    }
+
+    /// Reads a text file, as Markdown, Pleco flashcards or a Quizlet export if it
+    /// looks like one and as delimited text otherwise.
+    pub fn text_file(
+        file_name: String,
+        bytes: Vec<u8>,
+        remembered: Option<&ColumnMapping>,
+    ) -> Self {
+        let encoding = detect_encoding(&bytes);
+        let (text, _) = decode(&bytes, encoding);
+        let lower = file_name.to_lowercase();
+        if lower.ends_with(".md") || lower.ends_with(".markdown") || looks_like_markdown(&text) {
+            let source = read_markdown(&text);
+            let mapping = initial_mapping(&source.rows, remembered);
+            return Self {
+                file_name,
+                format: ImportFormat::Markdown {
+                    text: DelimitedText::new(bytes, encoding),
+                },
+                source,
+                mapping,
+                strategy: MergeStrategy::default(),
+                target: ImportTarget::default(),
+            };
+        }
+        let mut pending = Self::delimited(file_name, bytes, encoding, remembered);
+        if looks_like_pleco(&text) {
+            pending.set_pleco(true);
+        } else if let Some(separators) = looks_like_quizlet(&text) {
+            pending.set_quizlet(Some(separators));
+        }
+        pending
+    }
+
+    pub fn delimited(
+        file_name: String,
+        bytes: Vec<u8>,
+        encoding: &'static Encoding,
+        remembered: Option<&ColumnMapping>,
+    ) -> Self {
+        let text = DelimitedText::new(bytes, encoding);
+        let source = read_csv_rows(&text.text, text.dialect);
+        let mapping = initial_mapping(&source.rows, remembered);
+        Self {
+            file_name,
+            format: ImportFormat::Delimited(text),
+            source,
+            mapping,
+            strategy: MergeStrategy::default(),
+            target: ImportTarget::default(),
+        }
+    }
+
+    /// Whether the import creates wordsets of its own instead of adding to the current one.
+    pub fn creates_wordsets(&self) -> bool {
+        self.target == ImportTarget::NewWordset
+    }
+
+    /// Whether the cards can go into the current wordset as well as a new one.
+    pub fn has_target_choice(&self) -> bool {
+        matches!(
+            self.format,
+            ImportFormat::Delimited(_)
+                | ImportFormat::Spreadsheet { .. }
+                | ImportFormat::Markdown { .. }
+                | ImportFormat::Quizlet { .. }
+                | ImportFormat::Pleco { .. }
+        )
+    }
+
+    /// Chooses where a text or spreadsheet import goes; Anki decks always create wordsets.
+    pub fn set_target(&mut self, target: ImportTarget) {
+        if self.has_target_choice() {
+            self.target = target;
+        }
+    }
+
+    /// Switches a spreadsheet import to another worksheet, keeping the column
+    /// mapping when the number of columns did not change.
+    pub fn set_sheet(&mut self, index: usize) {
+        let ImportFormat::Spreadsheet { sheets, selected } = &mut self.format else {
+            return;
+        };
+        let Some(sheet) = sheets.get(index) else {
+            return;
+        };
+        *selected = index;
+        self.source = sheet.rows.clone();
+        self.mapping = initial_mapping(&self.source.rows, Some(&self.mapping));
+    }
+
+    /// The wordsets the import creates with their cards, or `None` for formats that
+    /// are imported into the current wordset.
+    pub fn new_wordsets(&self) -> Option<Vec<(String, Vec<Flashcard>)>> {
+        let report = build_report(&self.source, &self.mapping);
+        match &self.format {
+            ImportFormat::Delimited(_)
+            | ImportFormat::Spreadsheet { .. }
+            | ImportFormat::Markdown { .. }
+            | ImportFormat::Quizlet { .. } => self
+                .creates_wordsets()
+                .then(|| vec![(file_stem(&self.file_name).to_string(), report.cards())]),
+            ImportFormat::Anki { deck_name } => Some(vec![(deck_name.clone(), report.cards())]),
+            ImportFormat::Pleco {
+                file_stem,
+                split_categories,
+                ..
+            } => self
+                .creates_wordsets()
+                .then(|| pleco_wordsets(&self.source, report, file_stem, *split_categories)),
+        }
+    }
+
+    /// The cards to add to the current wordset, with Pleco categories as tags.
+    pub fn cards(&self) -> Vec<Flashcard> {
+        let report = build_report(&self.source, &self.mapping);
+        match &self.format {
+            ImportFormat::Pleco { file_stem, .. } => {
+                pleco_wordsets(&self.source, report, file_stem, false)
+                    .into_iter()
+                    .flat_map(|(_, cards)| cards)
+                    .collect()
+            }
+            _ => report.cards(),
+        }
+    }
+
+    pub fn set_split_categories(&mut self, split: bool) {
+        if let ImportFormat::Pleco {
+            split_categories, ..
+        } = &mut self.format
+        {
+            *split_categories = split;
+        }
+    }
+
+    /// Reads delimited text as Pleco flashcards, or back as delimited text.
+    pub fn set_pleco(&mut self, pleco: bool) {
+        let text = match &self.format {
+            ImportFormat::Delimited(text) | ImportFormat::Pleco { text, .. } => text.clone(),
+            _ => return,
+        };
+        if pleco {
+            self.source = read_pleco(&text.text);
+            self.format = ImportFormat::Pleco {
+                text,
+                file_stem: file_stem(&self.file_name).to_string(),
+                split_categories: false,
+            };
+            self.target = ImportTarget::NewWordset;
+        } else {
+            self.source = read_csv_rows(&text.text, text.dialect);
+            self.format = ImportFormat::Delimited(text);
+        }
+        self.mapping = guess_mapping(&self.source.rows);
+    }
+
+    /// Reads delimited text as a Quizlet export split by `separators`, or back as
+    /// delimited text when `None`.
+    pub fn set_quizlet(&mut self, separators: Option<QuizletSeparators>) {
+        let text = match &self.format {
+            ImportFormat::Delimited(text) | ImportFormat::Quizlet { text, .. } => text.clone(),
+            _ => return,
+        };
+        match separators {
+            Some(separators) => {
+                self.source = read_quizlet(&text.text, &separators);
+                self.format = ImportFormat::Quizlet { text, separators };
+            }
+            None => {
+                self.source = read_csv_rows(&text.text, text.dialect);
+                self.format = ImportFormat::Delimited(text);
+            }
+        }
+        self.mapping = guess_mapping(&self.source.rows);
+    }
+
+    /// Decodes the file again with `encoding` and reads it the same way, keeping
+    /// the column mapping when the number of columns did not change.
+    pub fn set_encoding(&mut self, encoding: &'static Encoding) {
+        self.source = match &mut self.format {
+            ImportFormat::Delimited(text) => {
+                text.set_encoding(encoding);
+                read_csv_rows(&text.text, text.dialect)
+            }
+            ImportFormat::Markdown { text } => {
+                text.set_encoding(encoding);
+                read_markdown(&text.text)
+            }
+            ImportFormat::Pleco { text, .. } => {
+                text.set_encoding(encoding);
+                read_pleco(&text.text)
+            }
+            ImportFormat::Quizlet { text, separators } => {
+                text.set_encoding(encoding);
+                read_quizlet(&text.text, separators)
+            }
+            _ => return,
+        };
+        self.mapping = initial_mapping(&self.source.rows, Some(&self.mapping));
+    }
+
+    pub fn set_dialect(&mut self, dialect: CsvDialect) {
+        let ImportFormat::Delimited(delimited) = &mut self.format else {
+            return;
+        };
+        delimited.dialect = dialect;
+        self.source = read_csv_rows(&delimited.text, dialect);
+        self.mapping = initial_mapping(&self.source.rows, Some(&self.mapping));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub skipped: usize,
+}
+
+impl MergeSummary {
+    pub fn describe(&self) -> String {
+        format!(
+            "{} added, {} updated, {} unchanged, {} skipped as duplicates",
+            self.added, self.updated, self.unchanged, self.skipped
+        )
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct AcceptedRow {
+    pub line: usize,
+    pub card: Flashcard,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct ImportReport {
+    pub accepted: Vec<AcceptedRow>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+impl ImportReport {
+    pub fn cards(&self) -> Vec<Flashcard> {
+        self.accepted.iter().map(|row| row.card.clone()).collect()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.accepted
+            .iter()
+            .filter(|row| !row.warnings.is_empty())
+            .count()
+    }
+}
+
+pub fn strategy_label(strategy: MergeStrategy) -> &'static str {
+    match strategy {
+        MergeStrategy::SkipDuplicates => "Skip words that already exist",
+        MergeStrategy::UpdateExisting => "Update existing words, keep progress",
+        MergeStrategy::AppendAll => "Add everything, including duplicates",
+        MergeStrategy::SyncWithFile => "Sync with this file: add, change and remove words",
+    }
+}
+
+pub fn field_label(field: ColumnField) -> &'static str {
+    match field {
+        ColumnField::Word => "Word",
+        ColumnField::Pinyin => "Pinyin",
+        ColumnField::Translation => "Translation",
+        ColumnField::Known => "Known",
+        ColumnField::Tags => "Tags",
+        ColumnField::Ignore => "Ignore",
+    }
+}
+
+/// Recognises common header names for each card field.
+fn header_field(cell: &str) -> Option<ColumnField> {
+    match cell.trim().to_lowercase().as_str() {
+        "word" | "words" | "hanzi" | "character" | "characters" | "chinese" | "simplified"
+        | "traditional" | "term" | "front" => Some(ColumnField::Word),
+        "pinyin" | "reading" | "pronunciation" | "zhuyin" | "romanization" => {
+            Some(ColumnField::Pinyin)
+        }
+        "translation" | "meaning" | "definition" | "definitions" | "english" | "back" | "gloss" => {
+            Some(ColumnField::Translation)
+        }
+        "known" | "learned" | "mastered" | "status" => Some(ColumnField::Known),
+        "tags" | "tag" | "labels" => Some(ColumnField::Tags),
+        _ => None,
+    }
+}
+
+pub fn column_count(rows: &[Vec<String>]) -> usize {
+    rows.iter().map(Vec::len).max().unwrap_or(0)
+}
+
+/// Treats the first row as a header when most of its non-empty cells are known field names.
+pub fn detect_header(rows: &[Vec<String>]) -> bool {
+    let Some(first) = rows.first() else {
+        return false;
+    };
+    let filled = first.iter().filter(|cell| !cell.trim().is_empty()).count();
+    let recognised = first
+        .iter()
+        .filter(|cell| header_field(cell).is_some())
+        .count();
+    recognised > 0 && recognised * 2 >= filled
+}
+
+/// Maps each column to the field its header names, using every field at most once.
+pub fn fields_from_header(header: &[String], count: usize) -> Vec<ColumnField> {
+    let mut columns: Vec<ColumnField> = Vec::with_capacity(count);
+    for index in 0..count {
+        let field = header
+            .get(index)
+            .and_then(|cell| header_field(cell))
+            .filter(|field| !columns.contains(field))
+            .unwrap_or(ColumnField::Ignore);
+        columns.push(field);
+    }
+    columns
+}
+
+/// Picks a mapping from the header names if there is a header, otherwise the default order.
+pub fn guess_mapping(rows: &[Vec<String>]) -> ColumnMapping {
+    let count = column_count(rows);
+    let has_header = detect_header(rows);
+
+    let columns = if has_header {
+        fields_from_header(&rows[0], count)
+    } else {
+        (0..count)
+            .map(|index| {
+                DEFAULT_COLUMNS
+                    .get(index)
+                    .copied()
+                    .unwrap_or(ColumnField::Ignore)
+            })
+            .collect()
+    };
+
+    ColumnMapping {
+        has_header,
+        columns,
+    }
+}
+
+/// Uses a remembered mapping when it fits the rows, otherwise guesses one.
+pub fn initial_mapping(rows: &[Vec<String>], remembered: Option<&ColumnMapping>) -> ColumnMapping {
+    match remembered {
+        Some(mapping) if mapping.columns.len() == column_count(rows) => mapping.clone(),
+        _ => guess_mapping(rows),
+    }
+}
+
+pub fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{f900}'..='\u{faff}')
+}
+
+fn is_boolean(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "" | "true" | "false" | "yes" | "no" | "y" | "n" | "1" | "0" | "x" | "known" | "unknown"
+    )
+}
+
+pub fn parse_known(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "true" | "yes" | "y" | "1" | "x" | "known"
+    )
+}
+
+fn pinyin_warning(pinyin: &str) -> Option<String> {
+    if pinyin.chars().any(is_cjk) {
+        return Some("Pinyin contains Chinese characters".to_string());
+    }
+    let unexpected: String = pinyin
+        .chars()
+        .filter(|c| {
+            !(c.is_ascii_alphabetic()
+                || ('1'..='5').contains(c)
+                || " '’-·,.;/()".contains(*c)
+                || TONE_VOWELS.contains(*c))
+        })
+        .collect();
+    (!unexpected.is_empty()).then(|| format!("Pinyin contains unexpected characters: {unexpected}"))
+}
+
+/// Splits a tag cell on whitespace and commas, as Anki and most spreadsheets write them.
+pub fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in value.split(|c: char| c.is_whitespace() || c == ',') {
+        if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+fn card_from_row(row: &[String], mapping: &ColumnMapping) -> Flashcard {
+    let mut card = Flashcard {
+        word: String::new(),
+        pinyin: None,
+        translation: String::new(),
+        known: false,
+        tags: Vec::new(),
+    };
+    for (cell, field) in row.iter().zip(&mapping.columns) {
+        match field {
+            ColumnField::Word => card.word = cell.trim().to_string(),
+            ColumnField::Pinyin => {
+                let pinyin = cell.trim();
+                card.pinyin = (!pinyin.is_empty()).then(|| pinyin.to_string());
+            }
+            ColumnField::Translation => card.translation = cell.trim().to_string(),
+            ColumnField::Known => card.known = parse_known(cell),
+            ColumnField::Tags => card.tags = parse_tags(cell),
+            ColumnField::Ignore => {}
+        }
+    }
+    card
+}
+
+/// Turns source rows into cards, rejecting rows that cannot become a card and
+/// flagging accepted rows that look wrong.
+pub fn build_report(source: &SourceRows, mapping: &ColumnMapping) -> ImportReport {
+    let mut report = ImportReport {
+        accepted: Vec::new(),
+        rejected: source.errors.clone(),
+    };
+    let skip = usize::from(mapping.has_header);
+    let maps = |field: ColumnField| mapping.columns.contains(&field);
+
+    for (index, row) in source.rows.iter().enumerate().skip(skip) {
+        let line = source.line_numbers.get(index).copied().unwrap_or(index + 1);
+
+        if row.iter().all(|cell| cell.trim().is_empty()) {
+            report.rejected.push(RejectedRow {
+                line,
+                reason: "Empty row".to_string(),
+            });
+            continue;
+        }
+
+        let card = card_from_row(row, mapping);
+        if card.word.is_empty() {
+            report.rejected.push(RejectedRow {
+                line,
+                reason: "Missing word".to_string(),
+            });
+            continue;
+        }
+
+        let mut warnings = Vec::new();
+        if maps(ColumnField::Translation) && card.translation.is_empty() {
+            warnings.push("Missing translation".to_string());
+        }
+        if let Some(warning) = card.pinyin.as_deref().and_then(pinyin_warning) {
+            warnings.push(warning);
+        }
+        if let Some(value) = row
+            .iter()
+            .zip(&mapping.columns)
+            .find(|(_, field)| **field == ColumnField::Known)
+            .map(|(cell, _)| cell)
+            .filter(|cell| !is_boolean(cell))
+        {
+            warnings.push(format!(
+                "Known value \"{}\" not recognised, treated as unknown",
+                value.trim()
+            ));
+        }
+        if let Some(first) = report
+            .accepted
+            .iter()
+            .find(|accepted| accepted.card.word == card.word)
+        {
+            warnings.push(format!("Duplicate of line {}", first.line));
+        }
+
+        report.accepted.push(AcceptedRow {
+            line,
+            card,
+            warnings,
+        });
+    }
+
+    report.rejected.sort_by_key(|row| row.line);
+    report
+}
+
+/// Adds `incoming` cards to a wordset's unknown and known lists according to `strategy`.
+pub fn merge_cards(
+    unknown: &mut Vec<Flashcard>,
+    known: &mut Vec<Flashcard>,
+    incoming: Vec<Flashcard>,
+    strategy: MergeStrategy,
+) -> MergeSummary {
+    let mut summary = MergeSummary::default();
+
+    for card in incoming {
+        let existing = if strategy == MergeStrategy::AppendAll {
+            None
+        } else {
+            unknown
+                .iter_mut()
+                .chain(known.iter_mut())
+                .find(|existing| existing.word == card.word)
+        };
+
+        match (existing, strategy) {
+            (None, _) => {
+                summary.added += 1;
+                if card.known {
+                    known.push(card);
+                } else {
+                    unknown.push(card);
+                }
+            }
+            (Some(existing), MergeStrategy::UpdateExisting) => {
+                let before = existing.clone();
+                if card.pinyin.is_some() {
+                    existing.pinyin = card.pinyin;
+                }
+                if !card.translation.is_empty() {
+                    existing.translation = card.translation;
+                }
+                if !card.tags.is_empty() {
+                    existing.tags = card.tags;
+                }
+                if *existing == before {
+                    summary.unchanged += 1;
+                } else {
+                    summary.updated += 1;
+                }
+            }
+            (Some(_), _) => summary.skipped += 1,
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_report, detect_header, guess_mapping, initial_mapping, merge_cards, ImportFormat,
+        ImportTarget, MergeStrategy, PendingImport, SourceRows,
+    };
+    use crate::model::Flashcard;
+    use crate::model::{ColumnField, ColumnMapping};
+
+    fn rows(lines: &[&[&str]]) -> Vec<Vec<String>> {
+        lines
+            .iter()
+            .map(|line| line.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    fn source(rows: Vec<Vec<String>>) -> SourceRows {
+        SourceRows {
+            line_numbers: (1..=rows.len()).collect(),
+            rows,
+            errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn maps_columns_from_header_names() {
+        let rows = rows(&[
+            &["English", "Hanzi", "Notes", "Pinyin"],
+            &["aunt", "阿姨", "family", "āyí"],
+        ]);
+
+        let mapping = guess_mapping(&rows);
+
+        assert!(mapping.has_header);
+        assert_eq!(
+            mapping.columns,
+            [
+                ColumnField::Translation,
+                ColumnField::Word,
+                ColumnField::Ignore,
+                ColumnField::Pinyin
+            ]
+        );
+        let cards = build_report(&source(rows), &mapping).cards();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].word, "阿姨");
+        assert_eq!(cards[0].translation, "aunt");
+    }
+
+    #[test]
+    fn data_rows_are_not_headers() {
+        let rows = rows(&[&["阿姨", "āyí", "aunt", "true"]]);
+
+        assert!(!detect_header(&rows));
+        let mapping = guess_mapping(&rows);
+        assert_eq!(mapping.columns[3], ColumnField::Known);
+        assert!(build_report(&source(rows), &mapping).cards()[0].known);
+    }
+
+    #[test]
+    fn remembered_mapping_is_used_when_columns_match() {
+        let rows = rows(&[&["aunt", "阿姨"]]);
+        let remembered = ColumnMapping {
+            has_header: false,
+            columns: vec![ColumnField::Translation, ColumnField::Word],
+        };
+
+        assert_eq!(initial_mapping(&rows, Some(&remembered)), remembered);
+        let three_columns = ColumnMapping {
+            columns: vec![ColumnField::Ignore; 3],
+            ..remembered
+        };
+        assert_ne!(initial_mapping(&rows, Some(&three_columns)), three_columns);
+    }
+
+    #[test]
+    fn reports_rejected_rows_and_warnings() {
+        let source = source(rows(&[
+            &["word", "pinyin", "translation", "known"],
+            &["阿姨", "āyí", "aunt", "true"],
+            &["", "a", "ah", "false"],
+            &["", "", "", ""],
+            &["你好", "你好", "", "maybe"],
+            &["阿姨", "ayi9", "aunt", ""],
+        ]));
+        let mapping = guess_mapping(&source.rows);
+
+        let report = build_report(&source, &mapping);
+
+        assert_eq!(report.accepted.len(), 3);
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].line, 3);
+        assert_eq!(report.rejected[0].reason, "Missing word");
+        assert_eq!(report.rejected[1].reason, "Empty row");
+        assert!(report.accepted[0].warnings.is_empty());
+        assert_eq!(report.accepted[1].line, 5);
+        assert_eq!(report.accepted[1].warnings.len(), 3);
+        assert_eq!(report.accepted[2].warnings.len(), 2);
+        assert_eq!(report.warning_count(), 2);
+    }
+
+    fn card(word: &str, translation: &str, known: bool) -> Flashcard {
+        Flashcard {
+            word: word.to_string(),
+            pinyin: None,
+            translation: translation.to_string(),
+            known,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn merge_strategies_treat_existing_words_differently() {
+        let existing_unknown = vec![card("一", "one", false)];
+        let existing_known = vec![card("二", "two", true)];
+        let incoming = vec![
+            card("一", "one", false),
+            card("二", "2", false),
+            card("三", "three", false),
+        ];
+
+        let (mut unknown, mut known) = (existing_unknown.clone(), existing_known.clone());
+        let summary = merge_cards(
+            &mut unknown,
+            &mut known,
+            incoming.clone(),
+            MergeStrategy::SkipDuplicates,
+        );
+        assert_eq!((summary.added, summary.skipped), (1, 2));
+        assert_eq!(unknown.len(), 2);
+
+        let (mut unknown, mut known) = (existing_unknown.clone(), existing_known.clone());
+        let summary = merge_cards(
+            &mut unknown,
+            &mut known,
+            incoming.clone(),
+            MergeStrategy::UpdateExisting,
+        );
+        assert_eq!(
+            (summary.added, summary.updated, summary.unchanged),
+            (1, 1, 1)
+        );
+        assert_eq!(known[0].translation, "2");
+        assert!(known[0].known);
+
+        let (mut unknown, mut known) = (existing_unknown, existing_known);
+        let summary = merge_cards(&mut unknown, &mut known, incoming, MergeStrategy::AppendAll);
+        assert_eq!(summary.added, 3);
+        assert_eq!(unknown.len() + known.len(), 5);
+    }
+
+    #[test]
+    fn pasted_or_dropped_text_can_become_its_own_wordset() {
+        let bytes = "word,pinyin,translation\n你好,nǐ hǎo,hello\n"
+            .as_bytes()
+            .to_vec();
+        let mut pending = PendingImport::text_file("hsk1.csv".to_string(), bytes, None);
+
+        assert!(pending.target == ImportTarget::CurrentWordset);
+        assert!(pending.new_wordsets().is_none());
+
+        pending.set_target(ImportTarget::NewWordset);
+        let wordsets = pending.new_wordsets().unwrap();
+        assert_eq!(wordsets.len(), 1);
+        assert_eq!(wordsets[0].0, "hsk1");
+        assert_eq!(wordsets[0].1[0].word, "你好");
+
+        let pleco = "//HSK/HSK 1\n你好\tni3hao3\thello\n".as_bytes().to_vec();
+        let mut pending = PendingImport::text_file("Pasted text".to_string(), pleco, None);
+        assert!(pending.creates_wordsets());
+        pending.set_split_categories(true);
+        pending.set_target(ImportTarget::CurrentWordset);
+        assert!(!pending.creates_wordsets());
+        assert!(pending.new_wordsets().is_none());
+        let cards = pending.cards();
+        assert_eq!(cards[0].pinyin.as_deref(), Some("nǐhǎo"));
+        assert_eq!(cards[0].tags, ["HSK::HSK_1"]);
+    }
+
+    #[test]
+    fn markdown_notes_can_be_decoded_again() {
+        let (bytes, _, _) =
+            encoding_rs::GBK.encode("| Word | Translation |\n|---|---|\n| 你好 | hello |\n");
+        let mut pending =
+            PendingImport::text_file("notes.md".to_string(), bytes.into_owned(), None);
+
+        pending.set_encoding(encoding_rs::UTF_8);
+        assert_ne!(pending.source.rows[1][0], "你好");
+        pending.set_encoding(encoding_rs::GBK);
+        assert!(matches!(pending.format, ImportFormat::Markdown { .. }));
+        assert_eq!(pending.source.rows[1], ["你好", "hello"]);
+    }
+
+    #[test]
+    fn pleco_files_can_be_read_as_columns() {
+        let bytes = "//HSK\n你好\tni3hao3\thello\n".as_bytes().to_vec();
+        let mut pending = PendingImport::text_file("pleco.txt".to_string(), bytes, None);
+        assert!(matches!(pending.format, ImportFormat::Pleco { .. }));
+        assert_eq!(pending.source.rows[1], ["你好", "nǐhǎo", "hello", "HSK"]);
+
+        pending.set_pleco(false);
+        assert!(matches!(pending.format, ImportFormat::Delimited(_)));
+        pending.set_encoding(encoding_rs::GBK);
+        assert!(pending.source.rows.iter().any(|row| row[0] == "//HSK"));
+
+        let (bytes, _, _) = encoding_rs::GBK.encode("//HSK\n你好\tni3hao3\thello\n");
+        let mut pending =
+            PendingImport::text_file("pleco.txt".to_string(), bytes.into_owned(), None);
+        pending.set_encoding(encoding_rs::UTF_8);
+        pending.set_encoding(encoding_rs::GBK);
+        assert!(matches!(pending.format, ImportFormat::Pleco { .. }));
+        assert_eq!(pending.source.rows[1], ["你好", "nǐhǎo", "hello", "HSK"]);
+
+        let bytes = "word,translation\n// lesson 1\n你好,hello\n"
+            .as_bytes()
+            .to_vec();
+        let pending = PendingImport::text_file("notes.csv".to_string(), bytes, None);
+        assert!(matches!(pending.format, ImportFormat::Delimited(_)));
+    }
+
+    #[test]
+    fn quizlet_exports_are_recognised_and_can_be_read_as_columns() {
+        let bytes = "字 (zì)\tcharacter\n你好 (nǐ hǎo)\thello\n"
+            .as_bytes()
+            .to_vec();
+        let mut pending = PendingImport::text_file("quizlet.txt".to_string(), bytes, None);
+
+        assert!(matches!(pending.format, ImportFormat::Quizlet { .. }));
+        let cards = build_report(&pending.source, &pending.mapping).cards();
+        assert_eq!(cards[1].word, "你好");
+        assert_eq!(cards[1].pinyin.as_deref(), Some("nǐ hǎo"));
+
+        pending.set_encoding(encoding_rs::BIG5);
+        pending.set_encoding(encoding_rs::UTF_8);
+        assert!(matches!(pending.format, ImportFormat::Quizlet { .. }));
+        assert_eq!(pending.source.rows[2][0], "你好");
+
+        pending.set_quizlet(None);
+        assert!(matches!(pending.format, ImportFormat::Delimited(_)));
+        assert_eq!(pending.source.rows[0], ["字 (zì)", "character"]);
+    }
+}
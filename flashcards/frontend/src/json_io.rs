@@ -0,0 +1,181 @@
+//! Lossless, versioned JSON files holding a single wordset.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::Dataset;
+
+/// Value of the `format` member identifying wordset files.
+pub const WORDSET_FORMAT: &str = "language-flashcards-wordset";
+/// Newest version of the wordset file format this build reads and the one it writes.
+pub const WORDSET_VERSION: u64 = 1;
+
+#[derive(Serialize)]
+struct WordsetFileRef<'a> {
+    format: &'a str,
+    version: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exported_at: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    app_version: Option<&'a str>,
+    wordset: &'a Dataset,
+}
+
+#[derive(Deserialize)]
+struct WordsetFile {
+    wordset: Dataset,
+}
+
+fn wordset_file<'a>(dataset: &'a Dataset, exported_at: Option<&'a str>) -> WordsetFileRef<'a> {
+    WordsetFileRef {
+        format: WORDSET_FORMAT,
+        version: WORDSET_VERSION,
+        exported_at,
+        app_version: exported_at.map(|_| env!("CARGO_PKG_VERSION")),
+        wordset: dataset,
+    }
+}
+
+/// Serialises `dataset` with every card, its progress and its import settings.
+pub fn export_wordset_json(dataset: &Dataset, exported_at: &str) -> serde_json::Result<Vec<u8>> {
+    serde_json::to_vec_pretty(&wordset_file(dataset, Some(exported_at)))
+}
+
+/// The same file as [`export_wordset_json`] without whitespace, export time or app
+/// version, for share links.
+pub fn compact_wordset_json(dataset: &Dataset) -> serde_json::Result<Vec<u8>> {
+    serde_json::to_vec(&wordset_file(dataset, None))
+}
+
+/// Reads a wordset file, refusing other files and versions newer than [`WORDSET_VERSION`].
+pub fn read_wordset_json(text: &str) -> Result<Dataset, String> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|error| format!("This file is not valid JSON ({error})."))?;
+    if value.get("format").and_then(Value::as_str) != Some(WORDSET_FORMAT) {
+        return Err("This JSON file is not a wordset exported from this app.".to_string());
+    }
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| "The wordset file has no format version.".to_string())?;
+    if version == 0 || version > WORDSET_VERSION {
+        return Err(format!(
+            "The wordset file uses format version {version}, but this version of the app \
+            reads up to version {WORDSET_VERSION}. Update the app to import it."
+        ));
+    }
+
+    let dataset = serde_json::from_str::<WordsetFile>(text)
+        .map_err(|error| format!("The wordset file has incompatible content: {error}."))?
+        .wordset;
+
+    let mut problems = Vec::new();
+    if dataset.name.trim().is_empty() {
+        problems.push("the wordset has no name".to_string());
+    }
+    let without_word = dataset
+        .flashcards
+        .iter()
+        .chain(&dataset.known_cards)
+        .filter(|card| card.word.trim().is_empty())
+        .count();
+    if without_word > 0 {
+        problems.push(format!("{without_word} card(s) have no word"));
+    }
+    let misplaced = dataset.flashcards.iter().filter(|card| card.known).count()
+        + dataset
+            .known_cards
+            .iter()
+            .filter(|card| !card.known)
+            .count();
+    if misplaced > 0 {
+        problems.push(format!(
+            "{misplaced} card(s) are listed as known and unknown at the same time"
+        ));
+    }
+    if !problems.is_empty() {
+        return Err(format!(
+            "The wordset file has incompatible content: {}.",
+            problems.join(", ")
+        ));
+    }
+    Ok(dataset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compact_wordset_json, export_wordset_json, read_wordset_json};
+    use crate::model::{ColumnField, ColumnMapping, Dataset, Flashcard};
+
+    fn dataset() -> Dataset {
+        Dataset {
+            name: "HSK 1".to_string(),
+            flashcards: vec![Flashcard {
+                word: "你好".to_string(),
+                pinyin: Some("nǐ hǎo".to_string()),
+                translation: "hello".to_string(),
+                known: false,
+                tags: vec!["greeting".to_string()],
+            }],
+            known_cards: vec![Flashcard {
+                word: "谢谢".to_string(),
+                pinyin: None,
+                translation: "thank you".to_string(),
+                known: true,
+                tags: Vec::new(),
+            }],
+            column_mapping: Some(ColumnMapping {
+                has_header: true,
+                columns: vec![
+                    ColumnField::Word,
+                    ColumnField::Ignore,
+                    ColumnField::Translation,
+                ],
+            }),
+        }
+    }
+
+    fn error(text: &str) -> String {
+        read_wordset_json(text).err().unwrap_or_default()
+    }
+
+    #[test]
+    fn round_trips_every_field() {
+        let bytes = export_wordset_json(&dataset(), "2026-10-18T09:30:00.000Z").unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+
+        assert!(text.contains("\"version\": 1"));
+        assert!(text.contains("\"exported_at\": \"2026-10-18T09:30:00.000Z\""));
+        assert!(read_wordset_json(&text).unwrap() == dataset());
+    }
+
+    #[test]
+    fn compact_files_leave_out_export_details() {
+        let text = String::from_utf8(compact_wordset_json(&dataset()).unwrap()).unwrap();
+
+        assert!(text
+            .starts_with("{\"format\":\"language-flashcards-wordset\",\"version\":1,\"wordset\""));
+        assert!(read_wordset_json(&text).unwrap() == dataset());
+    }
+
+    #[test]
+    fn refuses_other_files_and_newer_versions() {
+        let text = String::from_utf8(export_wordset_json(&dataset(), "").unwrap()).unwrap();
+
+        let newer = text.replace("\"version\": 1", "\"version\": 2");
+        assert!(error(&newer).contains("format version 2"));
+        assert!(error("[1, 2]").contains("not a wordset"));
+        assert!(error("{").contains("not valid JSON"));
+    }
+
+    #[test]
+    fn reports_incompatible_content() {
+        let text = String::from_utf8(export_wordset_json(&dataset(), "").unwrap()).unwrap();
+
+        let missing = text.replace("\"translation\": \"hello\",", "");
+        assert!(error(&missing).contains("missing field `translation`"));
+
+        let misplaced = text.replace("\"known\": true", "\"known\": false");
+        assert!(error(&misplaced).contains("1 card(s) are listed as known"));
+    }
+}
@@ -1,8 +1,30 @@
+mod anki;
 mod app;
+mod bundle;
+mod cedict;
 mod components;
 mod csv_io;
+mod encoding;
+mod export;
+mod file_sync;
+mod history;
+mod import;
+mod json_io;
+mod markdown;
 mod model;
+mod pinyin;
+mod pleco;
+mod print;
+mod qr;
+mod quizlet;
+mod share;
+mod snapshots;
+mod sqlite;
 mod storage;
+mod sync;
+mod trash;
+mod validation;
+mod xlsx;
 
 fn main() {
     yew::Renderer::<app::App>::new().render();
@@ -0,0 +1,266 @@
+//! Markdown lesson notes, read from tables or bullets such as `- 你好 — nǐ hǎo — hello`.
+
+use crate::import::SourceRows;
+use crate::model::Flashcard;
+
+/// Dashes accepted between the parts of a bullet, longest first.
+const BULLET_SEPARATORS: [&str; 3] = ["—", "–", " - "];
+
+fn is_table_row(line: &str) -> bool {
+    line.contains('|')
+}
+
+/// The `|---|:---:|` line between the header and the body of a table.
+fn is_delimiter_row(line: &str) -> bool {
+    is_table_row(line)
+        && line.contains('-')
+        && line
+            .trim()
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+fn bullet(line: &str) -> Option<(&str, Option<bool>)> {
+    let line = line.trim_start();
+    let item = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .or_else(|| {
+            let (number, rest) = line.split_once(". ")?;
+            number.chars().all(|c| c.is_ascii_digit()).then_some(rest)
+        })?;
+    let item = item.trim();
+    for (checkbox, known) in [("[x]", true), ("[X]", true), ("[ ]", false)] {
+        if let Some(rest) = item.strip_prefix(checkbox) {
+            return Some((rest.trim(), Some(known)));
+        }
+    }
+    Some((item, None))
+}
+
+fn bullet_parts(item: &str) -> Vec<String> {
+    BULLET_SEPARATORS
+        .iter()
+        .find(|separator| item.contains(**separator))
+        .map_or_else(
+            || vec![item.to_string()],
+            |separator| item.split(separator).map(cell_text).collect(),
+        )
+}
+
+/// Cell text without emphasis, escaped pipes or `<br>`, with checkboxes as known values.
+fn cell_text(cell: &str) -> String {
+    let cell = cell.trim();
+    match cell {
+        "[x]" | "[X]" => return "x".to_string(),
+        "[ ]" => return String::new(),
+        _ => {}
+    }
+    let mut text = cell;
+    for marker in ["**", "__", "*", "_", "`"] {
+        if text.len() > 2 * marker.len() {
+            if let Some(inner) = text
+                .strip_prefix(marker)
+                .and_then(|rest| rest.strip_suffix(marker))
+            {
+                text = inner;
+            }
+        }
+    }
+    text.replace("\\|", "|")
+        .replace("<br>", "\n")
+        .replace("<br/>", "\n")
+        .trim()
+        .to_string()
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in line.chars() {
+        if c == '|' && !escaped {
+            cells.push(cell_text(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+        escaped = c == '\\';
+    }
+    cells.push(cell_text(&current));
+    cells
+}
+
+/// Recognises Markdown notes holding a table, or mostly bullets split by dashes.
+pub fn looks_like_markdown(text: &str) -> bool {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if lines.iter().any(|line| is_delimiter_row(line)) {
+        return true;
+    }
+    let bullets = lines
+        .iter()
+        .filter_map(|line| bullet(line))
+        .filter(|(item, _)| bullet_parts(item).len() > 1)
+        .count();
+    bullets > 0 && bullets * 2 >= lines.len()
+}
+
+/// Reads the rows of every table in `text` under the header of the first one, or
+/// else the bullets under a header row naming their columns.
+pub fn read_markdown(text: &str) -> SourceRows {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_start_matches('\u{feff}'))
+        .collect();
+    let mut source = SourceRows::default();
+
+    let has_table = lines.iter().any(|line| is_delimiter_row(line));
+    if has_table {
+        let mut index = 0;
+        while index + 1 < lines.len() {
+            if !is_table_row(lines[index]) || !is_delimiter_row(lines[index + 1]) {
+                index += 1;
+                continue;
+            }
+            let header = table_cells(lines[index]);
+            // Later tables repeating the first one's header continue it.
+            if source.rows.first() != Some(&header) {
+                source.rows.push(header);
+                source.line_numbers.push(index + 1);
+            }
+            index += 2;
+            while index < lines.len() && is_table_row(lines[index]) {
+                source.rows.push(table_cells(lines[index]));
+                source.line_numbers.push(index + 1);
+                index += 1;
+            }
+        }
+        return source;
+    }
+
+    source.rows.push(
+        ["Word", "Pinyin", "Translation", "Known"]
+            .map(str::to_string)
+            .to_vec(),
+    );
+    source.line_numbers.push(0);
+    for (index, line) in lines.iter().enumerate() {
+        let Some((item, known)) = bullet(line) else {
+            continue;
+        };
+        let mut parts = bullet_parts(item);
+        if parts.len() == 2 {
+            parts.insert(1, String::new());
+        }
+        parts.resize(3, String::new());
+        parts.push(if known == Some(true) { "x" } else { "" }.to_string());
+        source.rows.push(parts);
+        source.line_numbers.push(index + 1);
+    }
+    source
+}
+
+fn escape_cell(text: &str) -> String {
+    text.trim()
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// Writes the cards as a Markdown table with a task checkbox for the known state.
+pub fn export_markdown(name: &str, cards: &[Flashcard]) -> Vec<u8> {
+    let with_tags = cards.iter().any(|card| !card.tags.is_empty());
+    let mut text = format!(
+        "# {}\n\n| Known | Word | Pinyin | Translation |",
+        name.trim()
+    );
+    let mut delimiter = "| :---: | --- | --- | --- |".to_string();
+    if with_tags {
+        text.push_str(" Tags |");
+        delimiter.push_str(" --- |");
+    }
+    text.push('\n');
+    text.push_str(&delimiter);
+    text.push('\n');
+    for card in cards {
+        text.push_str(&format!(
+            "| {} | {} | {} | {} |",
+            if card.known { "[x]" } else { "[ ]" },
+            escape_cell(&card.word),
+            escape_cell(card.pinyin.as_deref().unwrap_or_default()),
+            escape_cell(&card.translation)
+        ));
+        if with_tags {
+            text.push_str(&format!(" {} |", escape_cell(&card.tags.join(" "))));
+        }
+        text.push('\n');
+    }
+    text.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_markdown, looks_like_markdown, read_markdown};
+    use crate::import::{build_report, guess_mapping, SourceRows};
+    use crate::model::Flashcard;
+
+    fn cards(source: &SourceRows) -> Vec<Flashcard> {
+        build_report(source, &guess_mapping(&source.rows)).cards()
+    }
+
+    #[test]
+    fn reads_tables_by_their_header() {
+        let text = "# Lesson 3\n\nSome notes.\n\n\
+            | Hanzi | Meaning | Pinyin |\n\
+            |:--|:--|:--|\n\
+            | **你好** | hello \\| hi | nǐ hǎo |\n\n\
+            More notes.\n\n\
+            Hanzi | Meaning | Pinyin\n\
+            ---|---|---\n\
+            谢谢 | thanks<br>thank you | xièxie\n";
+        assert!(looks_like_markdown(text));
+
+        let source = read_markdown(text);
+        assert_eq!(source.line_numbers, [5, 7, 13]);
+        let cards = cards(&source);
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].word, "你好");
+        assert_eq!(cards[0].translation, "hello | hi");
+        assert_eq!(cards[1].pinyin.as_deref(), Some("xièxie"));
+        assert_eq!(cards[1].translation, "thanks\nthank you");
+    }
+
+    #[test]
+    fn reads_bullets_and_task_lists() {
+        let text = "Vocabulary:\n- 你好 — nǐ hǎo — hello\n- [x] 谢谢 – xièxie – thanks\n* 再见 - goodbye\n";
+        assert!(looks_like_markdown(text));
+        assert!(!looks_like_markdown("你好,nǐ hǎo,hello\n- a dash\n"));
+
+        let cards = cards(&read_markdown(text));
+        assert_eq!(cards.len(), 3);
+        assert_eq!(cards[0].pinyin.as_deref(), Some("nǐ hǎo"));
+        assert!(!cards[0].known);
+        assert!(cards[1].known);
+        assert_eq!(cards[2].pinyin, None);
+        assert_eq!(cards[2].translation, "goodbye");
+    }
+
+    #[test]
+    fn exported_tables_import_again() {
+        let source = read_markdown("- [x] 你好 — nǐ hǎo — hello | hi\n- 谢谢 — xièxie — thanks\n");
+        let original = cards(&source);
+        let text = String::from_utf8(export_markdown("HSK 1", &original)).unwrap();
+
+        assert!(text.starts_with(
+            "# HSK 1\n\n| Known | Word | Pinyin | Translation |\n| :---: | --- | --- | --- |\n\
+            | [x] | 你好 | nǐ hǎo | hello \\| hi |\n"
+        ));
+        assert!(cards(&read_markdown(&text)) == original);
+    }
+}
@@ -1,46 +1,59 @@
+use std::rc::Rc;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Hash)]
 pub struct Flashcard {
     pub word: String,
     pub pinyin: Option<String>,
     pub translation: String,
     #[serde(default)]
     pub known: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
-#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Hash)]
 pub struct Dataset {
     pub name: String,
     pub flashcards: Vec<Flashcard>,
     pub known_cards: Vec<Flashcard>,
+    /// Column mapping used for the last import, offered again for the next one.
+    #[serde(default)]
+    pub column_mapping: Option<ColumnMapping>,
 }
 
-#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Hash)]
+pub enum ColumnField {
+    Word,
+    Pinyin,
+    Translation,
+    Known,
+    Tags,
+    Ignore,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug, Hash)]
+pub struct ColumnMapping {
+    pub has_header: bool,
+    pub columns: Vec<ColumnField>,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub enum FlashcardStage {
+    #[default]
     First,
     Second,
     Third,
 }
 
-impl Default for FlashcardStage {
-    fn default() -> Self {
-        Self::First
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub enum StudyDirection {
+    #[default]
     Normal,
     Reverse,
 }
 
-impl Default for StudyDirection {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 #[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct PersistedState {
     pub flashcards: Vec<Flashcard>,
@@ -51,3 +64,69 @@ pub struct PersistedState {
     #[serde(default)]
     pub current_dataset: String,
 }
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct TrashedDataset {
+    pub dataset: Dataset,
+    pub deleted_at: f64,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct TrashedCard {
+    pub card: Flashcard,
+    pub dataset: String,
+    pub deleted_at: f64,
+}
+
+fn default_retention_days() -> u32 {
+    30
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct Trash {
+    #[serde(default)]
+    pub datasets: Vec<TrashedDataset>,
+    #[serde(default)]
+    pub cards: Vec<TrashedCard>,
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+}
+
+impl Default for Trash {
+    fn default() -> Self {
+        Self {
+            datasets: Vec::new(),
+            cards: Vec::new(),
+            retention_days: default_retention_days(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SnapshotKind {
+    Hourly,
+    Daily,
+    Manual,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct AutoSnapshot {
+    pub taken_at: f64,
+    pub kind: SnapshotKind,
+    /// Shared by the snapshots of different kinds taken at the same moment.
+    pub datasets: Rc<Vec<Dataset>>,
+}
+
+/// Snapshots as stored, keeping each copy of the wordsets only once.
+#[derive(Deserialize, Serialize, Default)]
+pub struct StoredSnapshots {
+    pub copies: Vec<Vec<Dataset>>,
+    pub snapshots: Vec<StoredSnapshot>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct StoredSnapshot {
+    pub taken_at: f64,
+    pub kind: SnapshotKind,
+    pub copy: usize,
+}
@@ -0,0 +1,170 @@
+//! Conversion between numbered pinyin (`ni3 hao3`) and pinyin with tone marks (`nǐ hǎo`).
+
+const TONE_MARKS: [(char, [char; 4]); 12] = [
+    ('a', ['ā', 'á', 'ǎ', 'à']),
+    ('e', ['ē', 'é', 'ě', 'è']),
+    ('i', ['ī', 'í', 'ǐ', 'ì']),
+    ('o', ['ō', 'ó', 'ǒ', 'ò']),
+    ('u', ['ū', 'ú', 'ǔ', 'ù']),
+    ('ü', ['ǖ', 'ǘ', 'ǚ', 'ǜ']),
+    ('A', ['Ā', 'Á', 'Ǎ', 'À']),
+    ('E', ['Ē', 'É', 'Ě', 'È']),
+    ('I', ['Ī', 'Í', 'Ǐ', 'Ì']),
+    ('O', ['Ō', 'Ó', 'Ǒ', 'Ò']),
+    ('U', ['Ū', 'Ú', 'Ǔ', 'Ù']),
+    ('Ü', ['Ǖ', 'Ǘ', 'Ǚ', 'Ǜ']),
+];
+
+fn is_vowel(c: char) -> bool {
+    "aeiouüAEIOUÜ".contains(c) || unmark(c).is_some()
+}
+
+fn mark(base: char, tone: u32) -> char {
+    TONE_MARKS
+        .iter()
+        .find(|(plain, _)| *plain == base)
+        .and_then(|(_, marks)| marks.get(tone.wrapping_sub(1) as usize))
+        .copied()
+        .unwrap_or(base)
+}
+
+fn unmark(c: char) -> Option<(char, u32)> {
+    TONE_MARKS.iter().find_map(|(plain, marks)| {
+        marks
+            .iter()
+            .position(|mark| *mark == c)
+            .map(|index| (*plain, index as u32 + 1))
+    })
+}
+
+/// Puts the tone mark of a numbered syllable on the vowel the standard rules pick:
+/// `a` or `e` if present, the `o` of `ou`, otherwise the last vowel.
+fn mark_syllable(syllable: &str, tone: u32) -> String {
+    let syllable = syllable
+        .replace("u:", "ü")
+        .replace('v', "ü")
+        .replace("U:", "Ü")
+        .replace('V', "Ü");
+    if !(1..=4).contains(&tone) {
+        return syllable;
+    }
+    let lower = syllable.to_lowercase();
+    let chars: Vec<char> = syllable.chars().collect();
+    let lower_chars: Vec<char> = lower.chars().collect();
+    let target = lower_chars
+        .iter()
+        .position(|c| *c == 'a')
+        .or_else(|| lower_chars.iter().position(|c| *c == 'e'))
+        .or_else(|| lower.find("ou").map(|byte| lower[..byte].chars().count()))
+        .or_else(|| lower_chars.iter().rposition(|c| is_vowel(*c)));
+    chars
+        .iter()
+        .enumerate()
+        .map(|(index, c)| {
+            if Some(index) == target {
+                mark(*c, tone)
+            } else {
+                *c
+            }
+        })
+        .collect()
+}
+
+/// Converts numbered pinyin such as `ni3hao3` or `lu:4` to tone marks (`nǐhǎo`, `lǜ`).
+pub fn numbered_to_marked(pinyin: &str) -> String {
+    let mut output = String::with_capacity(pinyin.len());
+    let mut syllable = String::new();
+    for c in pinyin.chars() {
+        if c.is_ascii_alphabetic() || c == 'ü' || c == 'Ü' || (c == ':' && !syllable.is_empty()) {
+            syllable.push(c);
+        } else if let Some(tone) = c.to_digit(10).filter(|_| !syllable.is_empty()) {
+            output.push_str(&mark_syllable(&syllable, tone));
+            syllable.clear();
+        } else {
+            output.push_str(&mark_syllable(&syllable, 0));
+            syllable.clear();
+            output.push(c);
+        }
+    }
+    output.push_str(&mark_syllable(&syllable, 0));
+    output
+}
+
+/// Converts pinyin with tone marks to numbered pinyin. A toned syllable ends after
+/// its vowels and an `n`, `ng` or `r` not followed by a vowel.
+pub fn marked_to_numbered(pinyin: &str) -> String {
+    let chars: Vec<char> = pinyin.chars().collect();
+    let vowel_at = |index: usize| chars.get(index).copied().is_some_and(is_vowel);
+    let mut output = String::with_capacity(pinyin.len() + 4);
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        index += 1;
+        let Some((base, tone)) = unmark(c) else {
+            match c {
+                'ü' => output.push_str("u:"),
+                'Ü' => output.push_str("U:"),
+                _ => output.push(c),
+            }
+            continue;
+        };
+
+        match base {
+            'ü' => output.push_str("u:"),
+            'Ü' => output.push_str("U:"),
+            _ => output.push(base),
+        }
+        let lower_base = base.to_ascii_lowercase();
+        if let Some(next) = chars.get(index) {
+            if matches!(
+                (lower_base, next.to_ascii_lowercase()),
+                ('a', 'i') | ('a', 'o') | ('e', 'i') | ('o', 'u')
+            ) {
+                output.push(*next);
+                index += 1;
+            }
+        }
+        let lower_at = |index: usize| chars.get(index).map(|c| c.to_ascii_lowercase());
+        if lower_at(index) == Some('n') && lower_at(index + 1) == Some('g') && !vowel_at(index + 2)
+        {
+            output.extend(&chars[index..index + 2]);
+            index += 2;
+        } else if lower_at(index) == Some('n') && !vowel_at(index + 1)
+            || lower_base == 'e' && lower_at(index) == Some('r') && !vowel_at(index + 1)
+        {
+            output.push(chars[index]);
+            index += 1;
+        }
+        output.push(char::from_digit(tone, 10).unwrap_or('5'));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{marked_to_numbered, numbered_to_marked};
+
+    #[test]
+    fn converts_between_tone_numbers_and_marks() {
+        for (numbered, marked) in [
+            ("ni3 hao3", "nǐ hǎo"),
+            ("xian4zai4", "xiànzài"),
+            ("lu:4se4", "lǜsè"),
+            ("Zhong1guo2", "Zhōngguó"),
+            ("xiu1xi5", "xiūxi"),
+            ("dui4", "duì"),
+            ("er4", "èr"),
+        ] {
+            assert_eq!(numbered_to_marked(numbered), marked);
+            assert_eq!(numbered_to_marked(marked), marked);
+        }
+        assert_eq!(numbered_to_marked("nv3"), "nǚ");
+        assert_eq!(marked_to_numbered("nǐ hǎo"), "ni3 hao3");
+        assert_eq!(marked_to_numbered("xiànzài"), "xian4zai4");
+        assert_eq!(marked_to_numbered("lǜsè"), "lu:4se4");
+        assert_eq!(marked_to_numbered("Zhōngguó"), "Zhong1guo2");
+        assert_eq!(marked_to_numbered("péngyou"), "peng2you");
+        assert_eq!(marked_to_numbered("èrshí"), "er4shi2");
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::import::{ImportReport, SourceRows};
+use crate::model::Flashcard;
+use crate::pinyin::{marked_to_numbered, numbered_to_marked};
+
+/// Column of [`read_pleco`] rows holding the `//Category` the card was listed under.
+pub const CATEGORY_COLUMN: usize = 3;
+const NEWLINE: char = '\u{eab1}';
+
+fn is_numbered_pinyin(value: &str) -> bool {
+    let value = value.trim();
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == ':')
+        && value
+            .as_bytes()
+            .windows(2)
+            .any(|pair| pair[0].is_ascii_alphabetic() && (b'1'..=b'5').contains(&pair[1]))
+}
+
+/// Pleco writes categories as `//Path/Name`, without a space after the slashes
+/// that would make it an ordinary comment.
+fn is_category(line: &str) -> bool {
+    line.strip_prefix("//").is_some_and(|name| {
+        !name.trim().is_empty() && !name.starts_with(char::is_whitespace) && !name.contains('\t')
+    })
+}
+
+/// Recognises Pleco's flashcard export: `//Category` lines and tab-separated cards,
+/// and nothing else. Without categories the first card and most of the others must
+/// have numbered pinyin in their second column, so that a header row or a plain
+/// TSV file is not taken for Pleco's.
+pub fn looks_like_pleco(text: &str) -> bool {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_start_matches('\u{feff}'))
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let (categories, cards): (Vec<&str>, Vec<&str>) =
+        lines.iter().partition(|line| line.starts_with("//"));
+    if cards.is_empty() || !cards.iter().all(|line| line.contains('\t')) {
+        return false;
+    }
+    if !categories.is_empty() {
+        return categories.iter().all(|line| is_category(line));
+    }
+    let numbered = |line: &str| line.split('\t').nth(1).is_some_and(is_numbered_pinyin);
+    let count = cards.iter().filter(|line| numbered(line)).count();
+    numbered(cards[0]) && count * 2 >= cards.len()
+}
+
+/// Reads Pleco flashcards into rows of word, pinyin with tone marks, definition and
+/// category, after a header row naming those columns.
+pub fn read_pleco(text: &str) -> SourceRows {
+    let mut source = SourceRows::default();
+    source.rows.push(
+        ["Word", "Pinyin", "Definition", "Category"]
+            .map(str::to_string)
+            .to_vec(),
+    );
+    source.line_numbers.push(0);
+
+    let mut category = String::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}');
+        if let Some(name) = line.strip_prefix("//") {
+            category = name.trim().to_string();
+            continue;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut cells = line.split('\t');
+        let headword = cells.next().unwrap_or_default();
+        // Headwords are written as simplified[traditional].
+        let word = headword.split('[').next().unwrap_or(headword).trim();
+        let pinyin = numbered_to_marked(cells.next().unwrap_or_default().trim());
+        let definition = cells
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace(NEWLINE, "; ")
+            .trim()
+            .to_string();
+        source.line_numbers.push(index + 1);
+        source
+            .rows
+            .push(vec![word.to_string(), pinyin, definition, category.clone()]);
+    }
+    source
+}
+
+/// Tag for a Pleco category path such as `HSK/HSK 1` (`HSK::HSK_1`).
+pub fn category_tag(category: &str) -> String {
+    category
+        .split('/')
+        .map(|part| part.trim().replace(' ', "_"))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Groups imported cards into wordsets: one per category when `split_categories`
+/// is set, otherwise a single wordset named `file_stem` with categories as tags.
+pub fn pleco_wordsets(
+    source: &SourceRows,
+    report: ImportReport,
+    file_stem: &str,
+    split_categories: bool,
+) -> Vec<(String, Vec<Flashcard>)> {
+    let categories: HashMap<usize, &str> = source
+        .line_numbers
+        .iter()
+        .zip(&source.rows)
+        .map(|(line, row)| (*line, row.get(CATEGORY_COLUMN).map_or("", String::as_str)))
+        .collect();
+
+    let mut wordsets: Vec<(String, Vec<Flashcard>)> = Vec::new();
+    for row in report.accepted {
+        let category = categories.get(&row.line).copied().unwrap_or_default();
+        let mut card = row.card;
+        let name = if split_categories && !category.is_empty() {
+            category.replace('/', " / ")
+        } else {
+            let tag = category_tag(category);
+            if !tag.is_empty() && !card.tags.contains(&tag) {
+                card.tags.push(tag);
+            }
+            file_stem.to_string()
+        };
+        match wordsets.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, cards)) => cards.push(card),
+            None => wordsets.push((name, vec![card])),
+        }
+    }
+    wordsets
+}
+
+/// Writes cards in Pleco's flashcard import format under a `//name` category.
+pub fn export_pleco(name: &str, cards: &[Flashcard]) -> Vec<u8> {
+    let clean = |value: &str| value.replace(['\t', '\r'], " ").replace('\n', "; ");
+    let mut text = format!("//{}\n", clean(name).replace(" / ", "/"));
+    for card in cards {
+        text.push_str(&format!(
+            "{}\t{}\t{}\n",
+            clean(&card.word),
+            marked_to_numbered(&clean(card.pinyin.as_deref().unwrap_or_default())),
+            clean(&card.translation)
+        ));
+    }
+    text.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_pleco, looks_like_pleco, pleco_wordsets, read_pleco};
+    use crate::import::{build_report, guess_mapping};
+
+    #[test]
+    fn reads_categories_as_wordsets_or_tags() {
+        let text = "//HSK/HSK 1\n你好\tni3hao3\thello\n爱[愛]\tai4\tlove\n//Food\n苹果\tping2guo3\tapple\n";
+        assert!(looks_like_pleco(text));
+        assert!(looks_like_pleco("你好\tni3hao3\thello\n"));
+        assert!(!looks_like_pleco("你好,nǐ hǎo,hello\n"));
+        assert!(!looks_like_pleco("// my list\n你好,ni3hao3,hello\n"));
+        assert!(!looks_like_pleco(
+            "// exported cards\n你好\tni3hao3\thello\n"
+        ));
+        assert!(!looks_like_pleco(
+            "word\tpinyin\tmeaning\n你好\tni3hao3\thello\n"
+        ));
+
+        let source = read_pleco(text);
+        let mapping = guess_mapping(&source.rows);
+        let report = || build_report(&source, &mapping);
+
+        let split = pleco_wordsets(&source, report(), "pleco", true);
+        assert_eq!(split.len(), 2);
+        assert_eq!(split[0].0, "HSK / HSK 1");
+        assert_eq!(split[0].1[1].word, "爱");
+        assert_eq!(split[0].1[1].pinyin.as_deref(), Some("ài"));
+        assert_eq!(split[1].0, "Food");
+
+        let tagged = pleco_wordsets(&source, report(), "pleco", false);
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].1[0].tags, ["HSK::HSK_1"]);
+        assert_eq!(tagged[0].1[2].tags, ["Food"]);
+    }
+
+    #[test]
+    fn exports_numbered_pinyin_under_a_category() {
+        let source = read_pleco("你好\tni3hao3\thello\n");
+        let cards = build_report(&source, &guess_mapping(&source.rows)).cards();
+
+        assert_eq!(
+            String::from_utf8(export_pleco("HSK / HSK 1", &cards)).unwrap(),
+            "//HSK/HSK 1\n你好\tni3hao3\thello\n"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Print-ready HTML sheets of flashcards to cut out, fronts and backs on alternate pages.
+
+use js_sys::{Array, JsString};
+use wasm_bindgen::JsValue;
+use web_sys::{Blob, BlobPropertyBag, Url};
+
+use crate::csv_io::{field_text, trigger_csv_download};
+use crate::export::{scoped_dataset, ExportScope};
+use crate::import::field_label;
+use crate::model::{ColumnField, Dataset, Flashcard};
+
+/// Size of the printed cards on an A4 page.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum CardSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+pub const CARD_SIZES: [CardSize; 3] = [CardSize::Small, CardSize::Medium, CardSize::Large];
+
+pub const PRINT_FIELDS: [ColumnField; 4] = [
+    ColumnField::Word,
+    ColumnField::Pinyin,
+    ColumnField::Translation,
+    ColumnField::Tags,
+];
+
+pub fn card_size_label(size: CardSize) -> &'static str {
+    match size {
+        CardSize::Small => "Small (21 per page)",
+        CardSize::Medium => "Medium (10 per page)",
+        CardSize::Large => "Large (6 per page)",
+    }
+}
+
+/// Columns and rows per page, and card width and height in millimetres.
+fn layout(size: CardSize) -> (usize, usize, u32, u32) {
+    match size {
+        CardSize::Small => (3, 7, 63, 38),
+        CardSize::Medium => (2, 5, 95, 55),
+        CardSize::Large => (2, 3, 95, 92),
+    }
+}
+
+/// Everything chosen in the print dialog.
+#[derive(Clone, PartialEq)]
+pub struct PrintOptions {
+    pub size: CardSize,
+    pub scope: ExportScope,
+    /// Fields on the front of each card; the first is printed largest.
+    pub front: Vec<ColumnField>,
+    pub back: Vec<ColumnField>,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            size: CardSize::default(),
+            scope: ExportScope::default(),
+            front: vec![ColumnField::Word],
+            back: vec![ColumnField::Pinyin, ColumnField::Translation],
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn card_html(fields: Option<Vec<String>>) -> String {
+    let Some(fields) = fields else {
+        return "<div class=\"card empty\"></div>".to_string();
+    };
+    let lines: String = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(index, text)| {
+            let class = if index == 0 { "primary" } else { "secondary" };
+            format!("<div class=\"{class}\">{}</div>", escape_html(text))
+        })
+        .collect();
+    format!("<div class=\"card\">{lines}</div>")
+}
+
+/// An HTML document of the chosen cards that opens the print dialog once loaded.
+pub fn print_sheet_html(dataset: &Dataset, options: &PrintOptions) -> String {
+    let (columns, rows, width, height) = layout(options.size);
+    let scoped = scoped_dataset(dataset, &options.scope);
+    let cards: Vec<_> = scoped
+        .flashcards
+        .iter()
+        .chain(&scoped.known_cards)
+        .collect();
+    let side = |card: Option<&&Flashcard>, fields: &[ColumnField]| {
+        card_html(card.map(|card| {
+            fields
+                .iter()
+                .map(|field| field_text(card, *field))
+                .collect()
+        }))
+    };
+
+    let mut pages = String::new();
+    for page in cards.chunks(columns * rows) {
+        let filled_rows = page.len().div_ceil(columns);
+        let mut fronts = String::new();
+        let mut backs = String::new();
+        for row in 0..filled_rows {
+            for column in 0..columns {
+                fronts.push_str(&side(page.get(row * columns + column), &options.front));
+                // Mirror each row so the backs line up when the sheet is turned over.
+                backs.push_str(&side(
+                    page.get(row * columns + columns - 1 - column),
+                    &options.back,
+                ));
+            }
+        }
+        pages.push_str(&format!(
+            "<section class=\"page\">{fronts}</section>\n<section class=\"page back\">{backs}</section>\n"
+        ));
+    }
+
+    let describe = |fields: &[ColumnField]| {
+        fields
+            .iter()
+            .map(|field| field_label(*field))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"zh\">\n<head>\n<meta charset=\"utf-8\">\n\
+        <title>{title}</title>\n<style>\n\
+        @page {{ size: A4; margin: 10mm; }}\n\
+        body {{ margin: 0; font-family: \"Noto Sans SC\", \"Microsoft YaHei\", sans-serif; }}\n\
+        .note {{ margin: 10mm; color: #555; }}\n\
+        .page {{ display: grid; grid-template-columns: repeat({columns}, {width}mm); \
+        grid-auto-rows: {height}mm; justify-content: center; break-after: page; }}\n\
+        .card {{ box-sizing: border-box; border: 0.2mm dashed #999; padding: 3mm; overflow: hidden; \
+        display: flex; flex-direction: column; align-items: center; justify-content: center; \
+        gap: 1.5mm; text-align: center; }}\n\
+        .back .card {{ border-color: transparent; }}\n\
+        .primary {{ font-size: {primary}mm; }}\n\
+        .secondary {{ font-size: {secondary}mm; color: #333; }}\n\
+        @media print {{ .note {{ display: none; }} }}\n\
+        </style>\n</head>\n<body>\n\
+        <p class=\"note\">{title}: {count} cards. Fronts: {front}. Backs: {back}. \
+        Print double-sided and flip on the long edge so that each back lands behind its front.</p>\n\
+        {pages}\
+        <script>window.addEventListener(\"load\", () => window.print());</script>\n\
+        </body>\n</html>\n",
+        title = escape_html(&dataset.name),
+        count = cards.len(),
+        front = describe(&options.front),
+        back = describe(&options.back),
+        primary = height / 4,
+        secondary = (height / 10).max(3),
+    )
+}
+
+/// Opens the sheet in a new tab, where it brings up the print dialog, or
+/// downloads it when pop-ups are blocked.
+pub fn open_print_view(html: &str, file_name: &str) -> Result<(), JsValue> {
+    let parts = Array::of1(&JsString::from(html));
+    let properties = BlobPropertyBag::new();
+    properties.set_type("text/html");
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &properties)?;
+    // The URL is not revoked: the print view needs it for as long as it stays open.
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("window unavailable"))?;
+    if window.open_with_url_and_target(&url, "_blank")?.is_none() {
+        Url::revoke_object_url(&url)?;
+        trigger_csv_download(html.as_bytes(), file_name)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{print_sheet_html, CardSize, PrintOptions};
+    use crate::export::ExportScope;
+    use crate::model::{ColumnField, Dataset, Flashcard};
+
+    fn dataset(count: usize) -> Dataset {
+        Dataset {
+            name: "HSK <1>".to_string(),
+            flashcards: (0..count)
+                .map(|index| Flashcard {
+                    word: format!("字{index}"),
+                    pinyin: Some("zì".to_string()),
+                    translation: format!("meaning {index}"),
+                    known: false,
+                    tags: Vec::new(),
+                })
+                .collect(),
+            known_cards: Vec::new(),
+            column_mapping: None,
+        }
+    }
+
+    fn cards_of(page: &str) -> Vec<&str> {
+        page.split("<div class=\"card")
+            .skip(1)
+            .map(|card| {
+                card.split("<div class=\"primary\">")
+                    .nth(1)
+                    .and_then(|text| text.split('<').next())
+                    .unwrap_or("")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn backs_mirror_each_row_of_fronts() {
+        let options = PrintOptions {
+            size: CardSize::Medium,
+            front: vec![ColumnField::Word],
+            back: vec![ColumnField::Translation],
+            ..PrintOptions::default()
+        };
+        let html = print_sheet_html(&dataset(3), &options);
+        let pages: Vec<&str> = html.split("<section class=\"page").skip(1).collect();
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(cards_of(pages[0]), ["字0", "字1", "字2", ""]);
+        assert_eq!(
+            cards_of(pages[1]),
+            ["meaning 1", "meaning 0", "", "meaning 2"]
+        );
+        assert!(html.contains("<title>HSK &lt;1&gt;</title>"));
+    }
+
+    #[test]
+    fn starts_a_new_sheet_when_a_page_is_full() {
+        let options = PrintOptions {
+            size: CardSize::Large,
+            ..PrintOptions::default()
+        };
+        let html = print_sheet_html(&dataset(7), &options);
+        assert_eq!(html.matches("<section class=\"page\">").count(), 2);
+        assert_eq!(html.matches("<section class=\"page back\">").count(), 2);
+
+        let known_only = PrintOptions {
+            scope: ExportScope::Known,
+            ..options
+        };
+        assert_eq!(
+            print_sheet_html(&dataset(7), &known_only)
+                .matches("<section")
+                .count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Wordsets moved between devices as a QR code of their share link or a series of codes.
+
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use qrcodegen::{QrCode, QrCodeEcc};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{HtmlVideoElement, MediaStream, MediaStreamConstraints, MediaStreamTrack};
+
+use crate::model::Dataset;
+use crate::share::{decode_wordset, encode_wordset, share_link, MAX_WORDSET_BYTES, SHARE_PREFIX};
+
+const CHUNK_PREFIX: &str = "LFQR1:";
+/// Characters per code, which keeps codes coarse enough to read off a screen.
+const CHUNK_LENGTH: usize = 800;
+/// Most codes in a series of a wordset no larger than a share link may unpack to.
+const MAX_PARTS: usize = MAX_WORDSET_BYTES as usize / CHUNK_LENGTH;
+const QUIET_ZONE: i32 = 4;
+
+/// FNV-1a hash of the encoded wordset, telling the codes of different wordsets apart.
+fn set_id(encoded: &str) -> String {
+    let hash = encoded.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    format!("{hash:08x}")
+}
+
+/// The text of each QR code carrying `dataset`: its share link when that fits in
+/// one code, otherwise numbered parts of the encoded wordset for the app's scanner.
+pub fn qr_payloads(page_url: &str, dataset: &Dataset) -> Result<Vec<String>, String> {
+    let link = share_link(page_url, dataset)?;
+    if link.len() <= CHUNK_LENGTH {
+        return Ok(vec![link]);
+    }
+    let encoded = encode_wordset(dataset)?;
+    let id = set_id(&encoded);
+    // Base64 is ASCII, so byte chunks never split a character.
+    let pieces: Vec<&[u8]> = encoded.as_bytes().chunks(CHUNK_LENGTH).collect();
+    Ok(pieces
+        .iter()
+        .enumerate()
+        .map(|(index, piece)| {
+            format!(
+                "{CHUNK_PREFIX}{id}:{}/{}:{}",
+                index + 1,
+                pieces.len(),
+                String::from_utf8_lossy(piece)
+            )
+        })
+        .collect())
+}
+
+/// An SVG image of the QR code holding `text`, one unit per module.
+pub fn qr_svg(text: &str) -> Result<String, String> {
+    let code = QrCode::encode_text(text, QrCodeEcc::Low)
+        .map_err(|_| "The text is too long for a QR code.".to_string())?;
+    let mut path = String::new();
+    for y in 0..code.size() {
+        for x in 0..code.size() {
+            if code.get_module(x, y) {
+                path.push_str(&format!("M{},{}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE));
+            }
+        }
+    }
+    let extent = code.size() + 2 * QUIET_ZONE;
+    Ok(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {extent} {extent}\" \
+        shape-rendering=\"crispEdges\"><rect width=\"100%\" height=\"100%\" fill=\"#fff\"/>\
+        <path d=\"{path}\" fill=\"#000\"/></svg>"
+    ))
+}
+
+/// The parts of a wordset scanned so far, in any order.
+#[derive(Clone, Default, PartialEq)]
+pub struct QrAssembly {
+    id: Option<String>,
+    parts: Vec<Option<String>>,
+}
+
+impl QrAssembly {
+    /// Adds the text of a scanned code, returning whether it was a part not seen before.
+    pub fn add(&mut self, text: &str) -> Result<bool, String> {
+        let text = text.trim();
+        let (id, part, total, piece) = if let Some(start) = text.find(SHARE_PREFIX) {
+            let encoded = &text[start + SHARE_PREFIX.len()..];
+            (set_id(encoded), 1, 1, encoded)
+        } else {
+            let not_ours = || "This QR code does not hold a wordset.".to_string();
+            let mut fields = text
+                .strip_prefix(CHUNK_PREFIX)
+                .ok_or_else(not_ours)?
+                .splitn(3, ':');
+            let (Some(id), Some(position), Some(piece)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(not_ours());
+            };
+            let (part, total) = position
+                .split_once('/')
+                .and_then(|(part, total)| Some((part.parse().ok()?, total.parse().ok()?)))
+                .filter(|(part, total): &(usize, usize)| {
+                    *total <= MAX_PARTS && (1..=*total).contains(part)
+                })
+                .ok_or_else(not_ours)?;
+            (id.to_string(), part, total, piece)
+        };
+
+        if self.id.as_ref().is_some_and(|current| *current != id) {
+            return Err(
+                "This QR code belongs to another wordset than the codes scanned so far. \
+                Start over to scan it instead."
+                    .to_string(),
+            );
+        }
+        if self.parts.len() != total {
+            if self.id.is_some() {
+                return Err("This QR code is damaged. Scan it again.".to_string());
+            }
+            self.parts = vec![None; total];
+        }
+        self.id = Some(id);
+        let slot = &mut self.parts[part - 1];
+        let is_new = slot.is_none();
+        *slot = Some(piece.to_string());
+        Ok(is_new)
+    }
+
+    pub fn progress(&self) -> (usize, usize) {
+        (
+            self.parts.iter().filter(|part| part.is_some()).count(),
+            self.parts.len(),
+        )
+    }
+
+    /// The wordset, once every code of the series has been scanned.
+    pub fn finish(&self) -> Option<Result<Dataset, String>> {
+        let encoded: String = self
+            .parts
+            .iter()
+            .map(Option::as_deref)
+            .collect::<Option<Vec<_>>>()?
+            .concat();
+        if encoded.is_empty() {
+            return None;
+        }
+        if self.id.as_deref() != Some(set_id(&encoded).as_str()) {
+            return Some(Err(
+                "The scanned codes do not fit together. Start over and scan them again."
+                    .to_string(),
+            ));
+        }
+        Some(decode_wordset(&encoded))
+    }
+}
+
+/// A `BarcodeDetector` reading QR codes, where the browser provides one.
+pub fn barcode_detector() -> Option<JsValue> {
+    let window = web_sys::window()?;
+    let class: Function = Reflect::get(&window, &"BarcodeDetector".into())
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    let options = Object::new();
+    Reflect::set(&options, &"formats".into(), &Array::of1(&"qr_code".into())).ok()?;
+    Reflect::construct(&class, &Array::of1(&options)).ok()
+}
+
+/// The text of every QR code `detector` finds in the current frame of `video`.
+pub async fn detect_codes(detector: &JsValue, video: &HtmlVideoElement) -> Vec<String> {
+    let found = async {
+        let detect: Function = Reflect::get(detector, &"detect".into())?.dyn_into()?;
+        let codes = JsFuture::from(Promise::from(detect.call1(detector, video)?)).await?;
+        Ok::<_, JsValue>(
+            Array::from(&codes)
+                .iter()
+                .filter_map(|code| Reflect::get(&code, &"rawValue".into()).ok()?.as_string())
+                .collect(),
+        )
+    };
+    // Frames before the video has started playing cannot be read.
+    found.await.unwrap_or_default()
+}
+
+/// Shows the back camera of the device in `video`.
+pub async fn start_camera(video: &HtmlVideoElement) -> Result<MediaStream, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("window unavailable"))?;
+    let facing = Object::new();
+    Reflect::set(&facing, &"facingMode".into(), &"environment".into())?;
+    let constraints = MediaStreamConstraints::new();
+    constraints.set_video(&facing);
+    let request = window
+        .navigator()
+        .media_devices()?
+        .get_user_media_with_constraints(&constraints)?;
+    let stream: MediaStream = JsFuture::from(request).await?.dyn_into()?;
+    video.set_src_object(Some(&stream));
+    JsFuture::from(video.play()?).await?;
+    Ok(stream)
+}
+
+pub fn stop_camera(stream: &MediaStream) {
+    for track in stream.get_tracks().iter() {
+        if let Ok(track) = track.dyn_into::<MediaStreamTrack>() {
+            track.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{qr_payloads, qr_svg, QrAssembly, CHUNK_PREFIX};
+    use crate::model::{Dataset, Flashcard};
+
+    fn dataset(count: usize) -> Dataset {
+        Dataset {
+            name: "HSK 1".to_string(),
+            // Varied words so that compression cannot fold them all into one code.
+            flashcards: (0..count)
+                .map(|index| Flashcard {
+                    word: format!("字{}", index * 7919 % 10007),
+                    pinyin: Some(format!("zi{}", index % 5)),
+                    translation: format!("meaning {}", index * 104_729 % 99_991),
+                    known: false,
+                    tags: Vec::new(),
+                })
+                .collect(),
+            known_cards: Vec::new(),
+            column_mapping: None,
+        }
+    }
+
+    #[test]
+    fn small_wordsets_fit_in_one_link() {
+        let codes = qr_payloads("https://example.org/", &dataset(3)).unwrap();
+        assert_eq!(codes.len(), 1);
+        assert!(codes[0].starts_with("https://example.org/#wordset="));
+
+        let mut assembly = QrAssembly::default();
+        assert_eq!(assembly.add(&codes[0]), Ok(true));
+        assert!(assembly.finish().unwrap().unwrap() == dataset(3));
+    }
+
+    #[test]
+    fn reassembles_a_series_scanned_in_any_order() {
+        let codes = qr_payloads("https://example.org/", &dataset(300)).unwrap();
+        assert!(codes.len() > 2);
+        assert!(codes.iter().all(|code| code.starts_with(CHUNK_PREFIX)));
+
+        let mut assembly = QrAssembly::default();
+        for code in codes.iter().skip(1).rev() {
+            assert_eq!(assembly.add(code), Ok(true));
+        }
+        assert_eq!(assembly.add(&codes[1]), Ok(false));
+        assert_eq!(assembly.progress(), (codes.len() - 1, codes.len()));
+        assert!(assembly.finish().is_none());
+
+        assembly.add(&codes[0]).unwrap();
+        assert!(assembly.finish().unwrap().unwrap() == dataset(300));
+        assert!(qr_svg(&codes[0]).unwrap().starts_with("<svg"));
+    }
+
+    #[test]
+    fn refuses_other_codes() {
+        let first = qr_payloads("https://example.org/", &dataset(300)).unwrap();
+        let second = qr_payloads("https://example.org/", &dataset(301)).unwrap();
+
+        let mut assembly = QrAssembly::default();
+        assembly.add(&first[0]).unwrap();
+        let error = assembly.add(&second[0]).err().unwrap_or_default();
+        assert!(error.contains("another wordset"));
+        let error = assembly
+            .add("https://example.com")
+            .err()
+            .unwrap_or_default();
+        assert!(error.contains("does not hold a wordset"));
+        assert!(assembly.add(&format!("{CHUNK_PREFIX}abc:0/2:xyz")).is_err());
+        let huge = format!("{CHUNK_PREFIX}abc:1/{}:xyz", usize::MAX);
+        assert!(QrAssembly::default().add(&huge).is_err());
+        assert_eq!(assembly.progress(), (1, first.len()));
+    }
+}
@@ -0,0 +1,220 @@
+//! Quizlet set text, as written by Quizlet's Export and read by its Import box.
+
+use crate::import::{is_cjk, SourceRows};
+use crate::model::Flashcard;
+use crate::pinyin::numbered_to_marked;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct QuizletSeparators {
+    pub term: String,
+    pub row: String,
+}
+
+impl Default for QuizletSeparators {
+    fn default() -> Self {
+        Self {
+            term: "\t".to_string(),
+            row: "\n".to_string(),
+        }
+    }
+}
+
+/// The separators Quizlet offers besides a custom one.
+pub const TERM_SEPARATORS: [(&str, &str); 2] = [("Tab", "\t"), ("Comma", ",")];
+pub const ROW_SEPARATORS: [(&str, &str); 2] = [("New line", "\n"), ("Semicolon", ";")];
+
+/// Latin letters with tone marks or tone numbers, which tells pinyin apart from
+/// English notes in brackets.
+fn is_pinyin(value: &str) -> bool {
+    value
+        .chars()
+        .any(|c| (c.is_alphabetic() && !c.is_ascii() && !is_cjk(c)) || matches!(c, '1'..='5'))
+        && value
+            .chars()
+            .all(|c| (c.is_alphabetic() && !is_cjk(c)) || matches!(c, ' ' | '\'' | ':' | '1'..='5'))
+}
+
+/// Splits a term written as `字 (zì)` or `字（zi4）` into the word and its pinyin,
+/// with tone numbers turned into tone marks. Other terms are returned whole.
+pub fn split_term(term: &str) -> (String, Option<String>) {
+    let term = term.trim();
+    let split = term
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once('('))
+        .or_else(|| {
+            term.strip_suffix('）')
+                .and_then(|rest| rest.rsplit_once('（'))
+        });
+    match split {
+        Some((word, pinyin)) if word.chars().any(is_cjk) && is_pinyin(pinyin.trim()) => (
+            word.trim().to_string(),
+            Some(numbered_to_marked(pinyin.trim())),
+        ),
+        _ => (term.to_string(), None),
+    }
+}
+
+fn entries<'a>(text: &'a str, separators: &QuizletSeparators) -> Vec<(usize, &'a str)> {
+    let text = text.trim_start_matches('\u{feff}');
+    let pieces: Vec<&str> = if separators.row.is_empty() {
+        vec![text]
+    } else {
+        text.split(separators.row.as_str()).collect()
+    };
+    let mut line = 1;
+    let mut entries = Vec::new();
+    for piece in pieces {
+        let entry = piece.trim_matches(['\r', '\n']);
+        if !entry.trim().is_empty() {
+            let leading = &piece[..piece.len() - piece.trim_start_matches(['\r', '\n']).len()];
+            entries.push((line + leading.matches('\n').count(), entry));
+        }
+        line += piece.matches('\n').count() + separators.row.matches('\n').count();
+    }
+    entries
+}
+
+fn split_entry<'a>(entry: &'a str, separators: &QuizletSeparators) -> (&'a str, &'a str) {
+    if separators.term.is_empty() {
+        return (entry, "");
+    }
+    entry
+        .split_once(separators.term.as_str())
+        .unwrap_or((entry, ""))
+}
+
+/// Recognises a Quizlet export whose terms mostly carry pinyin, as in `字 (zì)`.
+pub fn looks_like_quizlet(text: &str) -> Option<QuizletSeparators> {
+    TERM_SEPARATORS.iter().find_map(|(_, term)| {
+        let separators = QuizletSeparators {
+            term: term.to_string(),
+            row: "\n".to_string(),
+        };
+        let entries = entries(text, &separators);
+        let with_pinyin = entries
+            .iter()
+            .filter(|(_, entry)| {
+                let (term, definition) = split_entry(entry, &separators);
+                !definition.is_empty() && split_term(term).1.is_some()
+            })
+            .count();
+        (with_pinyin > 0 && with_pinyin * 2 >= entries.len()).then_some(separators)
+    })
+}
+
+/// Reads a Quizlet export into rows of word, pinyin and definition under a header row.
+pub fn read_quizlet(text: &str, separators: &QuizletSeparators) -> SourceRows {
+    let mut source = SourceRows::default();
+    source.rows.push(
+        ["Word", "Pinyin", "Definition"]
+            .map(str::to_string)
+            .to_vec(),
+    );
+    source.line_numbers.push(0);
+
+    for (line, entry) in entries(text, separators) {
+        let (term, definition) = split_entry(entry, separators);
+        let (word, pinyin) = split_term(term);
+        source.line_numbers.push(line);
+        source.rows.push(vec![
+            word,
+            pinyin.unwrap_or_default(),
+            definition.trim().to_string(),
+        ]);
+    }
+    source
+}
+
+/// Writes cards for Quizlet's Import box, replacing separators inside a card so
+/// that it stays whole.
+pub fn export_quizlet(cards: &[Flashcard], separators: &QuizletSeparators) -> Vec<u8> {
+    let clean = |value: &str, separator: &str| {
+        let value = value.replace(['\t', '\r'], " ").replace('\n', "; ");
+        match separator.trim() {
+            "" => value,
+            separator => value.replace(separator, " "),
+        }
+    };
+    let mut text = String::new();
+    for card in cards {
+        let term = match card.pinyin.as_deref().map(str::trim) {
+            Some(pinyin) if !pinyin.is_empty() => format!("{} ({pinyin})", card.word.trim()),
+            _ => card.word.trim().to_string(),
+        };
+        text.push_str(&clean(&clean(&term, &separators.term), &separators.row));
+        text.push_str(&separators.term);
+        text.push_str(&clean(&card.translation, &separators.row));
+        text.push_str(&separators.row);
+    }
+    text.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_quizlet, looks_like_quizlet, read_quizlet, split_term, QuizletSeparators};
+    use crate::import::{build_report, guess_mapping};
+
+    #[test]
+    fn splits_pinyin_out_of_terms() {
+        assert_eq!(
+            split_term("字 (zì)"),
+            ("字".to_string(), Some("zì".to_string()))
+        );
+        assert_eq!(
+            split_term("你好（ni3 hao3）"),
+            ("你好".to_string(), Some("nǐ hǎo".to_string()))
+        );
+        assert_eq!(split_term("字 (character)").1, None);
+        assert_eq!(split_term("苹果 (苹果)"), ("苹果 (苹果)".to_string(), None));
+        assert_eq!(
+            split_term("apple (fruit)"),
+            ("apple (fruit)".to_string(), None)
+        );
+        assert_eq!(split_term("字"), ("字".to_string(), None));
+    }
+
+    #[test]
+    fn reads_custom_separators() {
+        let separators = QuizletSeparators {
+            term: " - ".to_string(),
+            row: "\n\n".to_string(),
+        };
+        let text = "字 (zì) - character\n\n你好 - hello;\nhi\n\n\n\n谢谢 (xiè xie) - thanks";
+        let source = read_quizlet(text, &separators);
+
+        assert_eq!(source.line_numbers, [0, 1, 3, 8]);
+        let cards = build_report(&source, &guess_mapping(&source.rows)).cards();
+        assert_eq!(cards.len(), 3);
+        assert_eq!(cards[0].pinyin.as_deref(), Some("zì"));
+        assert_eq!(cards[1].pinyin, None);
+        assert_eq!(cards[1].translation, "hello;\nhi");
+        assert_eq!(cards[2].word, "谢谢");
+    }
+
+    #[test]
+    fn exports_what_the_importer_reads() {
+        let source = read_quizlet(
+            "字 (zì)\tcharacter\n你好\thello\n",
+            &QuizletSeparators::default(),
+        );
+        let cards = build_report(&source, &guess_mapping(&source.rows)).cards();
+        let text =
+            String::from_utf8(export_quizlet(&cards, &QuizletSeparators::default())).unwrap();
+
+        assert_eq!(text, "字 (zì)\tcharacter\n你好\thello\n");
+        assert_eq!(
+            looks_like_quizlet(&text),
+            Some(QuizletSeparators::default())
+        );
+        assert_eq!(looks_like_quizlet("字,zì,character\n"), None);
+
+        let separators = QuizletSeparators {
+            term: ",".to_string(),
+            row: ";".to_string(),
+        };
+        let text = String::from_utf8(export_quizlet(&cards, &separators)).unwrap();
+        assert_eq!(text, "字 (zì),character;你好,hello;");
+        let source = read_quizlet(&text, &separators);
+        assert_eq!(source.rows.len(), 3);
+    }
+}
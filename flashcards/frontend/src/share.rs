@@ -0,0 +1,163 @@
+//! Share links holding a compressed wordset in the URL fragment, which browsers never upload.
+
+use std::io::{Read, Write};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use wasm_bindgen::JsValue;
+
+use crate::json_io::{compact_wordset_json, read_wordset_json};
+use crate::model::Dataset;
+
+pub const SHARE_PREFIX: &str = "#wordset=";
+/// Length above which some email clients and chat apps cut links short.
+pub const LONG_LINK_LENGTH: usize = 8_000;
+/// Largest wordset file a link may unpack to, so a crafted link cannot fill memory.
+pub const MAX_WORDSET_BYTES: u64 = 16 * 1024 * 1024;
+
+/// The wordset file of `dataset`, DEFLATE-compressed and in URL-safe base64. Import
+/// settings are left out since they only describe the sender's file.
+pub fn encode_wordset(dataset: &Dataset) -> Result<String, String> {
+    let dataset = Dataset {
+        column_mapping: None,
+        ..dataset.clone()
+    };
+    let json = compact_wordset_json(&dataset)
+        .map_err(|error| format!("The wordset could not be encoded ({error})."))?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    let compressed = encoder
+        .write_all(&json)
+        .and_then(|()| encoder.finish())
+        .map_err(|error| format!("The wordset could not be compressed ({error})."))?;
+    Ok(URL_SAFE_NO_PAD.encode(compressed))
+}
+
+/// Reads a wordset written by [`encode_wordset`].
+pub fn decode_wordset(encoded: &str) -> Result<Dataset, String> {
+    let broken = || {
+        "The shared wordset is incomplete or damaged. Ask for it to be shared again.".to_string()
+    };
+    // Some apps percent-encode or pad the link; neither is part of the data.
+    let encoded = encoded.replace("%3D", "").replace('=', "");
+    let compressed = URL_SAFE_NO_PAD
+        .decode(encoded.trim())
+        .map_err(|_| broken())?;
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_WORDSET_BYTES + 1)
+        .read_to_end(&mut json)
+        .map_err(|_| broken())?;
+    if json.len() as u64 > MAX_WORDSET_BYTES {
+        return Err("The shared wordset is too large to open.".to_string());
+    }
+    let text = String::from_utf8(json).map_err(|_| broken())?;
+    read_wordset_json(&text)
+}
+
+/// A link to the page at `page_url` that offers `dataset` for import when opened.
+pub fn share_link(page_url: &str, dataset: &Dataset) -> Result<String, String> {
+    let page = page_url.split('#').next().unwrap_or_default();
+    Ok(format!("{page}{SHARE_PREFIX}{}", encode_wordset(dataset)?))
+}
+
+/// Reads the wordset in a URL fragment such as `location.hash`, or returns `None`
+/// when the fragment is not a share link.
+pub fn read_share_fragment(fragment: &str) -> Option<Result<Dataset, String>> {
+    fragment.strip_prefix(SHARE_PREFIX).map(decode_wordset)
+}
+
+pub fn current_page_url() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().href().ok())
+        .unwrap_or_default()
+}
+
+/// The wordset in the address of the page, if it was opened from a share link.
+pub fn shared_wordset_in_location() -> Option<Result<Dataset, String>> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    read_share_fragment(&hash)
+}
+
+/// Removes the share link from the address so that reloading does not offer it again.
+pub fn clear_share_fragment() -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("window unavailable"))?;
+    let location = window.location();
+    let page = format!("{}{}", location.pathname()?, location.search()?);
+    window
+        .history()?
+        .replace_state_with_url(&JsValue::NULL, "", Some(&page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_share_fragment, share_link, SHARE_PREFIX};
+    use crate::model::{ColumnMapping, Dataset, Flashcard};
+
+    fn dataset() -> Dataset {
+        let card = |word: &str, known: bool| Flashcard {
+            word: word.to_string(),
+            pinyin: Some("zì".to_string()),
+            translation: "character".to_string(),
+            known,
+            tags: vec!["lesson 1".to_string()],
+        };
+        Dataset {
+            name: "HSK 1".to_string(),
+            flashcards: (0..200)
+                .map(|index| card(&format!("字{index}"), false))
+                .collect(),
+            known_cards: vec![card("你好", true)],
+            column_mapping: None,
+        }
+    }
+
+    fn fragment(link: &str) -> &str {
+        &link[link.find('#').unwrap()..]
+    }
+
+    #[test]
+    fn links_carry_the_wordset_compressed() {
+        let link = share_link("https://example.org/cards/?lang=zh#old", &dataset()).unwrap();
+        assert!(link.starts_with(&format!("https://example.org/cards/?lang=zh{SHARE_PREFIX}")));
+        assert!(!fragment(&link)[SHARE_PREFIX.len()..].contains(['+', '/', '=']));
+        assert!(link.len() < 2_000);
+
+        let shared = read_share_fragment(fragment(&link)).unwrap().unwrap();
+        assert!(shared == dataset());
+    }
+
+    #[test]
+    fn leaves_out_import_settings() {
+        let mut dataset = dataset();
+        dataset.column_mapping = Some(ColumnMapping {
+            has_header: true,
+            columns: Vec::new(),
+        });
+        let link = share_link("https://example.org/", &dataset).unwrap();
+        let shared = read_share_fragment(fragment(&link)).unwrap().unwrap();
+        assert!(shared.column_mapping.is_none());
+    }
+
+    #[test]
+    fn reports_damaged_links_and_ignores_other_fragments() {
+        assert!(read_share_fragment("").is_none());
+        assert!(read_share_fragment("#help").is_none());
+
+        let link = share_link("https://example.org/", &dataset()).unwrap();
+        let truncated = &fragment(&link)[..40];
+        let error = read_share_fragment(truncated)
+            .unwrap()
+            .err()
+            .unwrap_or_default();
+        assert!(error.contains("incomplete or damaged"));
+
+        let error = read_share_fragment("#wordset=%%%")
+            .unwrap()
+            .err()
+            .unwrap_or_default();
+        assert!(error.contains("incomplete or damaged"));
+    }
+}
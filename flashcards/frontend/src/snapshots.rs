@@ -0,0 +1,235 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::model::{AutoSnapshot, Dataset, SnapshotKind, StoredSnapshot, StoredSnapshots};
+
+const HOUR_MS: f64 = 3_600_000.0;
+const DAY_MS: f64 = 24.0 * HOUR_MS;
+
+const HOURLY_KEPT: usize = 24;
+const DAILY_KEPT: usize = 7;
+const MANUAL_KEPT: usize = 5;
+
+const SCHEDULED: [SnapshotKind; 2] = [SnapshotKind::Hourly, SnapshotKind::Daily];
+
+fn interval(kind: SnapshotKind) -> f64 {
+    match kind {
+        SnapshotKind::Hourly => HOUR_MS,
+        SnapshotKind::Daily => DAY_MS,
+        SnapshotKind::Manual => 0.0,
+    }
+}
+
+fn kept(kind: SnapshotKind) -> usize {
+    match kind {
+        SnapshotKind::Hourly => HOURLY_KEPT,
+        SnapshotKind::Daily => DAILY_KEPT,
+        SnapshotKind::Manual => MANUAL_KEPT,
+    }
+}
+
+fn content_hash(datasets: &[Dataset]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    datasets.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Adds a snapshot of `datasets` and drops the oldest ones of that kind beyond its limit.
+pub fn push_snapshot(
+    snapshots: &mut Vec<AutoSnapshot>,
+    kind: SnapshotKind,
+    datasets: Rc<Vec<Dataset>>,
+    now: f64,
+) {
+    snapshots.push(AutoSnapshot {
+        taken_at: now,
+        kind,
+        datasets,
+    });
+
+    let excess = snapshots
+        .iter()
+        .filter(|snapshot| snapshot.kind == kind)
+        .count()
+        .saturating_sub(kept(kind));
+    let mut dropped = 0;
+    snapshots.retain(|snapshot| {
+        if snapshot.kind == kind && dropped < excess {
+            dropped += 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// When the newest hourly and daily snapshots were taken and a hash of what they
+/// hold, so that the app can tell when one is due without reading stored snapshots.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct SnapshotSchedule {
+    latest: [Option<(f64, u64)>; 2],
+}
+
+impl SnapshotSchedule {
+    pub fn from_snapshots(snapshots: &[AutoSnapshot]) -> Self {
+        let mut schedule = Self::default();
+        for (slot, kind) in schedule.latest.iter_mut().zip(SCHEDULED) {
+            *slot = snapshots
+                .iter()
+                .filter(|snapshot| snapshot.kind == kind)
+                .max_by(|a, b| a.taken_at.total_cmp(&b.taken_at))
+                .map(|latest| (latest.taken_at, content_hash(&latest.datasets)));
+        }
+        schedule
+    }
+
+    /// The kinds due a snapshot of `datasets`, which are then counted as taken. A
+    /// kind is due once its interval has passed since its newest snapshot, unless
+    /// nothing changed since then.
+    pub fn take_due(&mut self, datasets: &[Dataset], now: f64) -> Vec<SnapshotKind> {
+        let mut hash = None;
+        let mut due = Vec::new();
+        for (slot, kind) in self.latest.iter_mut().zip(SCHEDULED) {
+            if slot.is_some_and(|(taken_at, _)| now - taken_at < interval(kind)) {
+                continue;
+            }
+            if slot.is_none() && datasets.is_empty() {
+                continue;
+            }
+            let hash = *hash.get_or_insert_with(|| content_hash(datasets));
+            if slot.is_some_and(|(_, latest)| latest == hash) {
+                continue;
+            }
+            *slot = Some((now, hash));
+            due.push(kind);
+        }
+        due
+    }
+}
+
+/// The snapshots in their stored form, with shared copies of the wordsets kept once.
+pub fn pack_snapshots(snapshots: &[AutoSnapshot]) -> StoredSnapshots {
+    let mut shared: Vec<&Rc<Vec<Dataset>>> = Vec::new();
+    let mut stored = StoredSnapshots::default();
+    for snapshot in snapshots {
+        let copy = match shared
+            .iter()
+            .position(|datasets| Rc::ptr_eq(datasets, &snapshot.datasets))
+        {
+            Some(copy) => copy,
+            None => {
+                shared.push(&snapshot.datasets);
+                stored.copies.push(snapshot.datasets.to_vec());
+                shared.len() - 1
+            }
+        };
+        stored.snapshots.push(StoredSnapshot {
+            taken_at: snapshot.taken_at,
+            kind: snapshot.kind,
+            copy,
+        });
+    }
+    stored
+}
+
+pub fn unpack_snapshots(stored: StoredSnapshots) -> Vec<AutoSnapshot> {
+    let copies: Vec<_> = stored.copies.into_iter().map(Rc::new).collect();
+    stored
+        .snapshots
+        .into_iter()
+        .filter_map(|snapshot| {
+            Some(AutoSnapshot {
+                taken_at: snapshot.taken_at,
+                kind: snapshot.kind,
+                datasets: copies.get(snapshot.copy)?.clone(),
+            })
+        })
+        .collect()
+}
+
+pub fn card_count(dataset: &Dataset) -> usize {
+    dataset.flashcards.len() + dataset.known_cards.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{pack_snapshots, push_snapshot, unpack_snapshots, SnapshotSchedule, HOUR_MS};
+    use crate::model::{Dataset, SnapshotKind};
+
+    fn datasets(name: &str) -> Vec<Dataset> {
+        vec![Dataset {
+            name: name.to_string(),
+            flashcards: Vec::new(),
+            known_cards: Vec::new(),
+            column_mapping: None,
+        }]
+    }
+
+    #[test]
+    fn takes_snapshots_only_when_due_and_changed() {
+        let mut schedule = SnapshotSchedule::default();
+        assert!(schedule.take_due(&[], 0.0).is_empty());
+
+        let due = schedule.take_due(&datasets("A"), 0.0);
+        assert!(due == [SnapshotKind::Hourly, SnapshotKind::Daily]);
+        assert!(schedule.take_due(&datasets("B"), HOUR_MS / 2.0).is_empty());
+        assert!(schedule.take_due(&datasets("A"), 2.0 * HOUR_MS).is_empty());
+        assert!(schedule.take_due(&datasets("B"), 2.0 * HOUR_MS) == [SnapshotKind::Hourly]);
+
+        let mut snapshots = Vec::new();
+        push_snapshot(
+            &mut snapshots,
+            SnapshotKind::Hourly,
+            Rc::new(datasets("B")),
+            2.0 * HOUR_MS,
+        );
+        let mut restored = SnapshotSchedule::from_snapshots(&snapshots);
+        assert!(restored.take_due(&datasets("B"), 4.0 * HOUR_MS) == [SnapshotKind::Daily]);
+    }
+
+    #[test]
+    fn keeps_only_the_newest_snapshots_per_kind() {
+        let mut snapshots = Vec::new();
+        push_snapshot(
+            &mut snapshots,
+            SnapshotKind::Daily,
+            Rc::new(datasets("A")),
+            0.0,
+        );
+        for hour in 0..30 {
+            push_snapshot(
+                &mut snapshots,
+                SnapshotKind::Hourly,
+                Rc::new(datasets("A")),
+                f64::from(hour) * HOUR_MS,
+            );
+        }
+
+        assert_eq!(snapshots.len(), 25);
+        assert!(snapshots[0].kind == SnapshotKind::Daily);
+        assert_eq!(snapshots[1].taken_at, 6.0 * HOUR_MS);
+    }
+
+    #[test]
+    fn stores_snapshots_taken_together_once() {
+        let shared = Rc::new(datasets("A"));
+        let mut snapshots = Vec::new();
+        push_snapshot(&mut snapshots, SnapshotKind::Hourly, shared.clone(), 0.0);
+        push_snapshot(&mut snapshots, SnapshotKind::Daily, shared, 0.0);
+        push_snapshot(
+            &mut snapshots,
+            SnapshotKind::Manual,
+            Rc::new(datasets("B")),
+            1.0,
+        );
+
+        let stored = pack_snapshots(&snapshots);
+        assert_eq!(stored.copies.len(), 2);
+        let unpacked = unpack_snapshots(stored);
+        assert!(unpacked == snapshots);
+        assert!(Rc::ptr_eq(&unpacked[0].datasets, &unpacked[1].datasets));
+    }
+}
@@ -0,0 +1,345 @@
+//! A minimal read-only SQLite reader for the collection database inside Anki decks.
+
+use std::collections::HashSet;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Value {
+    pub fn as_integer(&self) -> i64 {
+        match self {
+            Value::Integer(value) => *value,
+            Value::Real(value) => *value as i64,
+            Value::Text(text) => text.trim().parse().unwrap_or_default(),
+            Value::Null | Value::Blob(_) => 0,
+        }
+    }
+
+    pub fn as_text(&self) -> String {
+        match self {
+            Value::Null => String::new(),
+            Value::Integer(value) => value.to_string(),
+            Value::Real(value) => value.to_string(),
+            Value::Text(text) => text.clone(),
+            Value::Blob(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        }
+    }
+}
+
+/// A row of a table: its rowid and column values in declaration order. Columns
+/// declared `INTEGER PRIMARY KEY` are stored as NULL and read back as the rowid.
+pub struct Row {
+    pub rowid: i64,
+    pub values: Vec<Value>,
+}
+
+impl Row {
+    pub fn get(&self, column: usize) -> &Value {
+        self.values.get(column).unwrap_or(&Value::Null)
+    }
+}
+
+const HEADER_MAGIC: &[u8] = b"SQLite format 3\0";
+const LEAF_TABLE_PAGE: u8 = 0x0d;
+const INTERIOR_TABLE_PAGE: u8 = 0x05;
+
+pub struct Database<'a> {
+    data: &'a [u8],
+    page_size: usize,
+    usable_size: usize,
+}
+
+fn read_varint(bytes: &[u8]) -> Option<(i64, usize)> {
+    let mut value: u64 = 0;
+    for (index, byte) in bytes.iter().take(9).enumerate() {
+        if index == 8 {
+            return Some((((value << 8) | u64::from(*byte)) as i64, 9));
+        }
+        value = (value << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Some((value as i64, index + 1));
+        }
+    }
+    None
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<usize, String> {
+    bytes
+        .get(at..at.saturating_add(2))
+        .map(|b| usize::from(u16::from_be_bytes([b[0], b[1]])))
+        .ok_or_else(|| "truncated SQLite page".to_string())
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<usize, String> {
+    bytes
+        .get(at..at.saturating_add(4))
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .ok_or_else(|| "truncated SQLite page".to_string())
+}
+
+/// Big-endian two's complement integer of 1 to 8 bytes, sign-extended to 64 bits.
+fn read_be(bytes: &[u8]) -> i64 {
+    let value = bytes
+        .iter()
+        .fold(0i64, |acc, byte| (acc << 8) | i64::from(*byte));
+    let unused = 64 - 8 * bytes.len() as u32;
+    (value << unused) >> unused
+}
+
+fn decode_record(payload: &[u8]) -> Option<Vec<Value>> {
+    let (header_size, mut offset) = read_varint(payload)?;
+    let header_size = usize::try_from(header_size).ok()?;
+    let mut serial_types = Vec::new();
+    while offset < header_size {
+        let (serial_type, length) = read_varint(payload.get(offset..)?)?;
+        serial_types.push(serial_type);
+        offset += length;
+    }
+
+    let mut body = header_size;
+    let mut values = Vec::with_capacity(serial_types.len());
+    for serial_type in serial_types {
+        let width = match serial_type {
+            0 | 8 | 9 => 0,
+            1..=4 => serial_type as usize,
+            5 => 6,
+            6 | 7 => 8,
+            n if n >= 12 => usize::try_from((n - 12) / 2).ok()?,
+            _ => return None,
+        };
+        let end = body.checked_add(width)?;
+        let bytes = payload.get(body..end)?;
+        body = end;
+        values.push(match serial_type {
+            0 => Value::Null,
+            8 => Value::Integer(0),
+            9 => Value::Integer(1),
+            1..=6 => Value::Integer(read_be(bytes)),
+            7 => Value::Real(f64::from_be_bytes(bytes.try_into().ok()?)),
+            n if n % 2 == 0 => Value::Blob(bytes.to_vec()),
+            _ => Value::Text(String::from_utf8_lossy(bytes).into_owned()),
+        });
+    }
+    Some(values)
+}
+
+impl<'a> Database<'a> {
+    pub fn open(data: &'a [u8]) -> Result<Self, String> {
+        if data.len() < 100 || !data.starts_with(HEADER_MAGIC) {
+            return Err("not an SQLite database".to_string());
+        }
+        let page_size = match u16::from_be_bytes([data[16], data[17]]) {
+            1 => 65536,
+            size => usize::from(size),
+        };
+        let usable_size = page_size.saturating_sub(usize::from(data[20]));
+        if page_size < 512 || usable_size < 480 {
+            return Err("invalid SQLite page size".to_string());
+        }
+        if data[56..60] != [0, 0, 0, 1] && data[56..60] != [0, 0, 0, 0] {
+            return Err("only UTF-8 SQLite databases are supported".to_string());
+        }
+        Ok(Self {
+            data,
+            page_size,
+            usable_size,
+        })
+    }
+
+    fn page(&self, number: usize) -> Result<&'a [u8], String> {
+        let start = number
+            .checked_sub(1)
+            .and_then(|index| index.checked_mul(self.page_size))
+            .ok_or("invalid page number")?;
+        start
+            .checked_add(self.page_size)
+            .and_then(|end| self.data.get(start..end))
+            .ok_or_else(|| format!("page {number} is past the end of the database"))
+    }
+
+    /// Reads a cell's payload, following overflow pages when it does not fit.
+    fn payload(&self, cell: &'a [u8], size: usize) -> Result<Vec<u8>, String> {
+        if size > self.data.len() {
+            return Err("corrupt table cell".to_string());
+        }
+        let usable = self.usable_size;
+        let max_local = usable - 35;
+        let local = if size <= max_local {
+            size
+        } else {
+            let min_local = (usable - 12) * 32 / 255 - 23;
+            let local = min_local + (size - min_local) % (usable - 4);
+            if local <= max_local {
+                local
+            } else {
+                min_local
+            }
+        };
+
+        let mut payload = cell
+            .get(..local)
+            .ok_or_else(|| "corrupt table cell".to_string())?
+            .to_vec();
+        if local < size {
+            let mut next = read_u32(cell, local)?;
+            while payload.len() < size && next != 0 {
+                let page = self.page(next)?;
+                next = read_u32(page, 0)?;
+                let take = (size - payload.len()).min(usable - 4);
+                payload.extend_from_slice(&page[4..4 + take]);
+            }
+        }
+        Ok(payload)
+    }
+
+    /// `visited` stops corrupt trees that link back to a page already read.
+    fn collect_rows(
+        &self,
+        number: usize,
+        rows: &mut Vec<Row>,
+        visited: &mut HashSet<usize>,
+        depth: usize,
+    ) -> Result<(), String> {
+        if depth > 64 {
+            return Err("table b-tree is too deep".to_string());
+        }
+        if !visited.insert(number) {
+            return Err(format!("page {number} is linked more than once"));
+        }
+        let page = self.page(number)?;
+        let header = if number == 1 { &page[100..] } else { page };
+        let kind = header[0];
+        let cell_count = read_u16(header, 3)?;
+        let header_size = match kind {
+            LEAF_TABLE_PAGE => 8,
+            INTERIOR_TABLE_PAGE => 12,
+            _ => return Err(format!("page {number} is not a table page")),
+        };
+
+        for index in 0..cell_count {
+            let pointer = header_size + index * 2;
+            let cell = page
+                .get(read_u16(header, pointer)?..)
+                .ok_or("corrupt cell pointer")?;
+
+            if kind == INTERIOR_TABLE_PAGE {
+                self.collect_rows(read_u32(cell, 0)?, rows, visited, depth + 1)?;
+            } else {
+                let (size, size_length) = read_varint(cell).ok_or("corrupt cell")?;
+                let (rowid, rowid_length) =
+                    read_varint(&cell[size_length..]).ok_or("corrupt cell")?;
+                let size = usize::try_from(size).map_err(|_| "corrupt cell")?;
+                let payload = self.payload(&cell[size_length + rowid_length..], size)?;
+                let values = decode_record(&payload).ok_or("corrupt record")?;
+                rows.push(Row { rowid, values });
+            }
+        }
+
+        if kind == INTERIOR_TABLE_PAGE {
+            self.collect_rows(read_u32(header, 8)?, rows, visited, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Every row of the table `name`, in rowid order.
+    pub fn table(&self, name: &str) -> Result<Vec<Row>, String> {
+        let mut schema = Vec::new();
+        self.collect_rows(1, &mut schema, &mut HashSet::new(), 0)?;
+        let root = schema
+            .iter()
+            .find(|row| row.get(0).as_text() == "table" && row.get(1).as_text() == name)
+            .map(|row| row.get(3).as_integer())
+            .ok_or_else(|| format!("table \"{name}\" not found"))?;
+
+        let root = usize::try_from(root).map_err(|_| format!("table \"{name}\" is corrupt"))?;
+        let mut rows = Vec::new();
+        self.collect_rows(root, &mut rows, &mut HashSet::new(), 0)?;
+        for row in &mut rows {
+            if let Some(first @ Value::Null) = row.values.first_mut() {
+                *first = Value::Integer(row.rowid);
+            }
+        }
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Database, Value};
+
+    fn database(statements: &str) -> Vec<u8> {
+        let path = std::env::temp_dir().join(format!(
+            "flashcards-sqlite-{}-{}.db",
+            std::process::id(),
+            statements.len()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let connection = rusqlite::Connection::open(&path).expect("open test database");
+        connection
+            .execute_batch(statements)
+            .expect("build test database");
+        drop(connection);
+        let bytes = std::fs::read(&path).expect("read test database");
+        let _ = std::fs::remove_file(&path);
+        bytes
+    }
+
+    #[test]
+    fn reads_rows_of_a_table() {
+        let bytes = database(
+            "CREATE TABLE other (x);
+             CREATE TABLE notes (id INTEGER PRIMARY KEY, word TEXT, count INTEGER, score REAL);
+             INSERT INTO notes VALUES (5, '你好', -3, 1.5), (9, NULL, 300000, 0);",
+        );
+        let database = Database::open(&bytes).unwrap();
+        let rows = database.table("notes").unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].values[0], Value::Integer(5));
+        assert_eq!(rows[0].values[1], Value::Text("你好".to_string()));
+        assert_eq!(rows[0].values[2], Value::Integer(-3));
+        assert_eq!(rows[0].values[3], Value::Real(1.5));
+        assert_eq!(rows[1].values[1], Value::Null);
+        assert_eq!(rows[1].values[2], Value::Integer(300000));
+        assert!(database.table("missing").is_err());
+    }
+
+    #[test]
+    fn follows_interior_and_overflow_pages() {
+        let bytes = database(
+            "PRAGMA page_size = 512;
+             CREATE TABLE notes (id INTEGER PRIMARY KEY, text TEXT);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 300)
+             INSERT INTO notes SELECT i, printf('%.*c', i * 7, 'x') FROM n;",
+        );
+        let rows = Database::open(&bytes).unwrap().table("notes").unwrap();
+
+        assert_eq!(rows.len(), 300);
+        assert!(rows
+            .iter()
+            .all(|row| row.get(1).as_text().len() == row.rowid as usize * 7));
+    }
+
+    #[test]
+    fn rejects_trees_that_link_back_to_their_pages() {
+        let mut bytes = database(
+            "PRAGMA page_size = 512;
+             CREATE TABLE notes (id INTEGER PRIMARY KEY, text TEXT);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+             INSERT INTO notes SELECT i, printf('%.*c', 40, 'x') FROM n;",
+        );
+        // The table's root on page 2 is an interior page; point its rightmost child
+        // back at itself.
+        assert_eq!(bytes[512], 0x05);
+        bytes[512 + 8..512 + 12].copy_from_slice(&2u32.to_be_bytes());
+
+        let error = Database::open(&bytes).unwrap().table("notes").err();
+        assert!(error.is_some_and(|error| error.contains("linked more than once")));
+    }
+}
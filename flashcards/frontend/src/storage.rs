@@ -1,22 +1,135 @@
 use gloo_storage::{LocalStorage, Storage};
+use indexed_db_futures::prelude::*;
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+use web_sys::DomException;
 
-use crate::model::{Dataset, PersistedState};
+use crate::model::{AutoSnapshot, Dataset, PersistedState, Trash};
+use crate::snapshots::{pack_snapshots, unpack_snapshots};
 
 const STORAGE_KEY: &str = "flashcards_app_state";
-const DATASETS_KEY: &str = "flashcards_datasets_list";
+pub const DATASETS_KEY: &str = "flashcards_datasets_list";
+pub const TRASH_KEY: &str = "flashcards_trash";
+const SNAPSHOTS_KEY: &str = "flashcards_snapshots";
+const SNAPSHOTS_DB: &str = "flashcards_snapshots";
+const SNAPSHOTS_STORE: &str = "snapshots";
+const DICTIONARY_DB: &str = "flashcards_dictionary";
+const DICTIONARY_STORE: &str = "files";
+const CEDICT_KEY: &str = "cedict";
+
+/// Saves `value` under `key`, explaining what was lost when storage is full.
+fn save_to_local_storage<T: Serialize>(key: &str, value: &T, what: &str) -> Result<(), String> {
+    LocalStorage::set(key, value).map_err(|_| {
+        format!(
+            "Your {what} could not be saved because the browser's storage is full. \
+            Empty the trash or delete wordsets you no longer need, or your latest changes \
+            will be lost when the page is closed."
+        )
+    })
+}
 
 pub fn load_persisted_state() -> Option<PersistedState> {
     LocalStorage::get(STORAGE_KEY).ok()
 }
 
-pub fn save_persisted_state(state: &PersistedState) {
-    let _ = LocalStorage::set(STORAGE_KEY, state);
+pub fn save_persisted_state(state: &PersistedState) -> Result<(), String> {
+    save_to_local_storage(STORAGE_KEY, state, "study progress")
 }
 
 pub fn load_datasets() -> Vec<Dataset> {
     LocalStorage::get(DATASETS_KEY).ok().unwrap_or_default()
 }
 
-pub fn save_datasets(datasets: &[Dataset]) {
-    let _ = LocalStorage::set(DATASETS_KEY, datasets);
+/// The wordsets exactly as stored, to tell cheaply whether another tab changed them.
+pub fn stored_datasets_json() -> Option<String> {
+    LocalStorage::raw().get_item(DATASETS_KEY).ok().flatten()
+}
+
+pub fn save_datasets(datasets: &[Dataset]) -> Result<(), String> {
+    save_to_local_storage(DATASETS_KEY, &datasets, "wordsets")
+}
+
+pub fn load_trash() -> Trash {
+    LocalStorage::get(TRASH_KEY).ok().unwrap_or_default()
+}
+
+pub fn save_trash(trash: &Trash) -> Result<(), String> {
+    save_to_local_storage(TRASH_KEY, trash, "trash")
+}
+
+async fn open_db(name: &str, store: &'static str) -> Result<IdbDatabase, DomException> {
+    let mut request = IdbDatabase::open_u32(name, 1)?;
+    request.set_on_upgrade_needed(Some(
+        move |event: &IdbVersionChangeEvent| -> Result<(), JsValue> {
+            if !event.db().object_store_names().any(|name| name == store) {
+                event.db().create_object_store(store)?;
+            }
+            Ok(())
+        },
+    ));
+    request.await
+}
+
+async fn read_text(db: &str, store: &'static str, key: &str) -> Option<String> {
+    let db = open_db(db, store).await.ok()?;
+    let transaction = db.transaction_on_one(store).ok()?;
+    let store = transaction.object_store(store).ok()?;
+    store.get_owned(key).ok()?.await.ok()??.as_string()
+}
+
+async fn write_text(
+    db: &str,
+    store: &'static str,
+    key: &str,
+    text: &str,
+) -> Result<(), DomException> {
+    let db = open_db(db, store).await?;
+    let transaction = db.transaction_on_one_with_mode(store, IdbTransactionMode::Readwrite)?;
+    transaction
+        .object_store(store)?
+        .put_key_val_owned(key, &JsValue::from_str(text))?;
+    transaction.await.into_result()
+}
+
+/// Snapshots are kept in IndexedDB, apart from the wordsets in localStorage, so
+/// that they can never use up the space the wordsets need.
+pub async fn load_snapshots() -> Vec<AutoSnapshot> {
+    read_text(SNAPSHOTS_DB, SNAPSHOTS_STORE, SNAPSHOTS_KEY)
+        .await
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .map(unpack_snapshots)
+        .unwrap_or_default()
+}
+
+pub async fn save_snapshots(snapshots: &[AutoSnapshot]) -> Result<(), String> {
+    let text = serde_json::to_string(&pack_snapshots(snapshots))
+        .map_err(|error| format!("Could not save the snapshots: {error}"))?;
+    write_text(SNAPSHOTS_DB, SNAPSHOTS_STORE, SNAPSHOTS_KEY, &text)
+        .await
+        .map_err(|error| format!("Could not save the snapshots: {}", error.message()))
+}
+
+/// The CC-CEDICT text saved by [`save_dictionary_text`]. The dictionary is kept in
+/// IndexedDB because it is larger than the localStorage quota.
+pub async fn load_dictionary_text() -> Option<String> {
+    read_text(DICTIONARY_DB, DICTIONARY_STORE, CEDICT_KEY).await
+}
+
+pub async fn save_dictionary_text(text: &str) -> Result<(), String> {
+    write_text(DICTIONARY_DB, DICTIONARY_STORE, CEDICT_KEY, text)
+        .await
+        .map_err(|error| format!("Could not save the dictionary: {}", error.message()))
+}
+
+pub async fn clear_dictionary_text() {
+    let clear = async {
+        let db = open_db(DICTIONARY_DB, DICTIONARY_STORE).await?;
+        let transaction =
+            db.transaction_on_one_with_mode(DICTIONARY_STORE, IdbTransactionMode::Readwrite)?;
+        transaction
+            .object_store(DICTIONARY_STORE)?
+            .delete_owned(CEDICT_KEY)?;
+        transaction.await.into_result()
+    };
+    let _: Result<(), DomException> = clear.await;
 }
@@ -0,0 +1,118 @@
+use crate::model::Dataset;
+use crate::trash::unique_dataset_name;
+
+pub struct MergeOutcome {
+    pub datasets: Vec<Dataset>,
+    /// Names of wordsets edited both here and in another tab.
+    pub conflicts: Vec<String>,
+}
+
+fn find<'a>(datasets: &'a [Dataset], name: &str) -> Option<&'a Dataset> {
+    datasets.iter().find(|dataset| dataset.name == name)
+}
+
+/// Three-way merge of the wordsets in this tab (`local`) with the ones another tab
+/// wrote to storage (`remote`), relative to what this tab last saw there (`base`).
+///
+/// Wordsets changed on one side only take that side's version. When both sides
+/// changed the same wordset, the local version keeps its name and the remote one
+/// is added next to it, so neither edit is lost.
+pub fn merge_datasets(base: &[Dataset], local: &[Dataset], remote: &[Dataset]) -> MergeOutcome {
+    let mut datasets = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut conflicting_remotes = Vec::new();
+
+    let names = local
+        .iter()
+        .chain(remote.iter())
+        .map(|dataset| dataset.name.as_str());
+
+    for name in names {
+        if find(&datasets, name).is_some() || conflicts.iter().any(|conflict| conflict == name) {
+            continue;
+        }
+
+        let base_version = find(base, name);
+        let local_version = find(local, name);
+        let remote_version = find(remote, name);
+
+        let merged = if local_version == remote_version || remote_version == base_version {
+            local_version
+        } else if local_version == base_version {
+            remote_version
+        } else {
+            conflicts.push(name.to_string());
+            if let Some(remote_version) = remote_version {
+                conflicting_remotes.push(remote_version.clone());
+            }
+            local_version
+        };
+
+        if let Some(dataset) = merged {
+            datasets.push(dataset.clone());
+        }
+    }
+
+    for mut dataset in conflicting_remotes {
+        dataset.name = unique_dataset_name(&dataset.name, "other tab", &datasets);
+        datasets.push(dataset);
+    }
+
+    MergeOutcome {
+        datasets,
+        conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge_datasets;
+    use crate::model::{Dataset, Flashcard};
+
+    fn dataset(name: &str, words: &[&str]) -> Dataset {
+        Dataset {
+            name: name.to_string(),
+            flashcards: words
+                .iter()
+                .map(|word| Flashcard {
+                    word: word.to_string(),
+                    pinyin: None,
+                    translation: String::new(),
+                    known: false,
+                    tags: Vec::new(),
+                })
+                .collect(),
+            known_cards: Vec::new(),
+            column_mapping: None,
+        }
+    }
+
+    #[test]
+    fn takes_changes_from_either_side() {
+        let base = vec![dataset("A", &["一"]), dataset("B", &["二"])];
+        let local = vec![dataset("A", &["一", "三"]), dataset("B", &["二"])];
+        let remote = vec![dataset("A", &["一"]), dataset("C", &["四"])];
+
+        let outcome = merge_datasets(&base, &local, &remote);
+
+        assert!(outcome.conflicts.is_empty());
+        let names: Vec<_> = outcome.datasets.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["A", "C"]);
+        assert_eq!(outcome.datasets[0].flashcards.len(), 2);
+    }
+
+    #[test]
+    fn keeps_both_versions_on_conflict() {
+        let base = vec![dataset("A", &["一"])];
+        let local = vec![dataset("A", &["一", "二"])];
+        let remote = vec![dataset("A", &["一", "三"])];
+
+        let outcome = merge_datasets(&base, &local, &remote);
+
+        assert_eq!(outcome.conflicts, ["A"]);
+        assert_eq!(outcome.datasets.len(), 2);
+        assert_eq!(outcome.datasets[0].flashcards[1].word, "二");
+        assert_eq!(outcome.datasets[1].name, "A (other tab)");
+        assert_eq!(outcome.datasets[1].flashcards[1].word, "三");
+    }
+}
@@ -0,0 +1,93 @@
+use crate::model::{Dataset, Trash};
+
+const DAY_MS: f64 = 86_400_000.0;
+
+pub const RETENTION_CHOICES: [u32; 4] = [7, 30, 90, 365];
+
+/// Drops trashed wordsets and cards deleted longer ago than the retention period,
+/// returning whether there were any.
+pub fn purge_expired(trash: &mut Trash, now: f64) -> bool {
+    let cutoff = now - f64::from(trash.retention_days) * DAY_MS;
+    let before = trash.datasets.len() + trash.cards.len();
+    trash.datasets.retain(|item| item.deleted_at >= cutoff);
+    trash.cards.retain(|item| item.deleted_at >= cutoff);
+    trash.datasets.len() + trash.cards.len() < before
+}
+
+/// Returns `name`, or `name (suffix)` / `name (suffix 2)` ... if it is already taken.
+pub fn unique_dataset_name(name: &str, suffix: &str, datasets: &[Dataset]) -> String {
+    let taken = |candidate: &str| datasets.iter().any(|dataset| dataset.name == candidate);
+    if !taken(name) {
+        return name.to_string();
+    }
+
+    let mut candidate = format!("{name} ({suffix})");
+    let mut counter = 2;
+    while taken(&candidate) {
+        candidate = format!("{name} ({suffix} {counter})");
+        counter += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{purge_expired, unique_dataset_name, DAY_MS};
+    use crate::model::{Dataset, Flashcard, Trash, TrashedCard, TrashedDataset};
+
+    fn dataset(name: &str) -> Dataset {
+        Dataset {
+            name: name.to_string(),
+            flashcards: Vec::new(),
+            known_cards: Vec::new(),
+            column_mapping: None,
+        }
+    }
+
+    #[test]
+    fn purges_items_older_than_retention() {
+        let now = 100.0 * DAY_MS;
+        let mut trash = Trash {
+            retention_days: 30,
+            datasets: vec![
+                TrashedDataset {
+                    dataset: dataset("old"),
+                    deleted_at: now - 31.0 * DAY_MS,
+                },
+                TrashedDataset {
+                    dataset: dataset("recent"),
+                    deleted_at: now - DAY_MS,
+                },
+            ],
+            cards: vec![TrashedCard {
+                card: Flashcard {
+                    word: "阿姨".to_string(),
+                    pinyin: None,
+                    translation: "aunt".to_string(),
+                    known: false,
+                    tags: Vec::new(),
+                },
+                dataset: "recent".to_string(),
+                deleted_at: now - 45.0 * DAY_MS,
+            }],
+        };
+
+        assert!(purge_expired(&mut trash, now));
+        assert!(!purge_expired(&mut trash, now));
+
+        assert_eq!(trash.datasets.len(), 1);
+        assert_eq!(trash.datasets[0].dataset.name, "recent");
+        assert!(trash.cards.is_empty());
+    }
+
+    #[test]
+    fn restored_names_avoid_collisions() {
+        let datasets = vec![dataset("HSK 1"), dataset("HSK 1 (restored)")];
+
+        assert_eq!(unique_dataset_name("HSK 2", "restored", &datasets), "HSK 2");
+        assert_eq!(
+            unique_dataset_name("HSK 1", "restored", &datasets),
+            "HSK 1 (restored 2)"
+        );
+    }
+}
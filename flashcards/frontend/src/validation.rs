@@ -0,0 +1,177 @@
+use crate::model::{Dataset, Flashcard, PersistedState};
+use crate::trash::unique_dataset_name;
+
+/// Sets each card's `known` flag to match the list it is stored in.
+/// Returns how many cards were changed.
+fn align_known_flags(flashcards: &mut [Flashcard], known_cards: &mut [Flashcard]) -> usize {
+    let mut fixed = 0;
+    for (cards, known) in [(flashcards, false), (known_cards, true)] {
+        for card in cards.iter_mut().filter(|card| card.known != known) {
+            card.known = known;
+            fixed += 1;
+        }
+    }
+    fixed
+}
+
+/// Checks the invariants of freshly loaded state, repairs what it can in place and
+/// returns a human-readable description of every repair.
+pub fn repair_state(persisted: &mut PersistedState, datasets: &mut Vec<Dataset>) -> Vec<String> {
+    let mut repairs = Vec::new();
+
+    let mut unique: Vec<Dataset> = Vec::with_capacity(datasets.len());
+    for mut dataset in datasets.drain(..) {
+        if dataset.name.trim().is_empty() {
+            dataset.name = unique_dataset_name("Untitled", "recovered", &unique);
+            repairs.push(format!(
+                "A wordset without a name was renamed to \"{}\".",
+                dataset.name
+            ));
+        } else if unique.iter().any(|existing| existing.name == dataset.name) {
+            let original = dataset.name.clone();
+            dataset.name = unique_dataset_name(&original, "duplicate", &unique);
+            repairs.push(format!(
+                "A second wordset named \"{original}\" was renamed to \"{}\".",
+                dataset.name
+            ));
+        }
+        unique.push(dataset);
+    }
+    *datasets = unique;
+
+    for dataset in datasets.iter_mut() {
+        let fixed = align_known_flags(&mut dataset.flashcards, &mut dataset.known_cards);
+        if fixed > 0 {
+            repairs.push(format!(
+                "Fixed the known state of {fixed} card(s) in \"{}\".",
+                dataset.name
+            ));
+        }
+    }
+
+    if !persisted.current_dataset.is_empty() {
+        match datasets
+            .iter()
+            .find(|dataset| dataset.name == persisted.current_dataset)
+        {
+            Some(dataset) => {
+                if dataset.flashcards != persisted.flashcards
+                    || dataset.known_cards != persisted.known_cards
+                {
+                    persisted.flashcards = dataset.flashcards.clone();
+                    persisted.known_cards = dataset.known_cards.clone();
+                    repairs.push(format!(
+                        "Reloaded the cards of \"{}\" from the saved wordset.",
+                        dataset.name
+                    ));
+                }
+            }
+            None if persisted.flashcards.is_empty() && persisted.known_cards.is_empty() => {
+                repairs.push(format!(
+                    "The selected wordset \"{}\" no longer exists and was deselected.",
+                    persisted.current_dataset
+                ));
+                persisted.current_dataset.clear();
+            }
+            None => {
+                align_known_flags(&mut persisted.flashcards, &mut persisted.known_cards);
+                datasets.push(Dataset {
+                    name: persisted.current_dataset.clone(),
+                    flashcards: persisted.flashcards.clone(),
+                    known_cards: persisted.known_cards.clone(),
+                    column_mapping: None,
+                });
+                repairs.push(format!(
+                    "The selected wordset \"{}\" was missing and was recreated from the last session.",
+                    persisted.current_dataset
+                ));
+            }
+        }
+    }
+
+    if persisted.current_index >= persisted.flashcards.len() && persisted.current_index != 0 {
+        repairs.push(format!(
+            "The saved card position {} was out of range and was reset.",
+            persisted.current_index + 1
+        ));
+        persisted.current_index = 0;
+    }
+
+    repairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::repair_state;
+    use crate::model::{Dataset, Flashcard, PersistedState};
+
+    fn card(word: &str, known: bool) -> Flashcard {
+        Flashcard {
+            word: word.to_string(),
+            pinyin: None,
+            translation: String::new(),
+            known,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn repairs_index_flags_and_duplicate_names() {
+        let dataset = Dataset {
+            name: "HSK 1".to_string(),
+            flashcards: vec![card("一", true)],
+            known_cards: vec![card("二", true)],
+            column_mapping: None,
+        };
+        let mut datasets = vec![dataset.clone(), dataset];
+        let mut persisted = PersistedState {
+            current_dataset: "HSK 1".to_string(),
+            current_index: 7,
+            ..PersistedState::default()
+        };
+
+        let repairs = repair_state(&mut persisted, &mut datasets);
+
+        assert_eq!(datasets[1].name, "HSK 1 (duplicate)");
+        assert!(!datasets[0].flashcards[0].known);
+        assert_eq!(persisted.flashcards.len(), 1);
+        assert!(!persisted.flashcards[0].known);
+        assert_eq!(persisted.current_index, 0);
+        assert_eq!(repairs.len(), 5);
+    }
+
+    #[test]
+    fn valid_state_needs_no_repairs() {
+        let mut datasets = vec![Dataset {
+            name: "HSK 1".to_string(),
+            flashcards: vec![card("一", false), card("二", false)],
+            known_cards: vec![card("三", true)],
+            column_mapping: None,
+        }];
+        let mut persisted = PersistedState {
+            current_dataset: "HSK 1".to_string(),
+            current_index: 1,
+            flashcards: datasets[0].flashcards.clone(),
+            known_cards: datasets[0].known_cards.clone(),
+            ..PersistedState::default()
+        };
+
+        assert!(repair_state(&mut persisted, &mut datasets).is_empty());
+    }
+
+    #[test]
+    fn recreates_missing_selected_wordset() {
+        let mut datasets = Vec::new();
+        let mut persisted = PersistedState {
+            current_dataset: "Lost".to_string(),
+            flashcards: vec![card("一", false)],
+            ..PersistedState::default()
+        };
+
+        let repairs = repair_state(&mut persisted, &mut datasets);
+
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(datasets[0].name, "Lost");
+        assert_eq!(datasets[0].flashcards.len(), 1);
+    }
+}
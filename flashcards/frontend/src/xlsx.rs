@@ -0,0 +1,209 @@
+//! Reading `.xlsx` workbooks for import and writing wordsets to formatted ones.
+
+use std::io::Cursor;
+
+use calamine::{Data, Reader, Xlsx};
+use rust_xlsxwriter::{Color, Format, FormatBorder, Workbook, XlsxError};
+
+use crate::csv_io::field_text;
+use crate::import::{field_label, SourceRows};
+use crate::model::{ColumnField, Flashcard};
+
+const HEADER_FILL: u32 = 0xF3E9DC;
+const KNOWN_FILL: u32 = 0xE2F3E5;
+
+#[derive(Clone, PartialEq)]
+pub struct Sheet {
+    pub name: String,
+    pub rows: SourceRows,
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Error(_) => String::new(),
+        cell => cell.to_string().trim().to_string(),
+    }
+}
+
+/// Reads every worksheet of an `.xlsx` file, numbering rows as Excel does.
+pub fn read_xlsx(bytes: &[u8]) -> Result<Vec<Sheet>, String> {
+    let mut workbook = Xlsx::new(Cursor::new(bytes))
+        .map_err(|_| "This file is not an Excel workbook (.xlsx).".to_string())?;
+    let mut sheets = Vec::new();
+    for name in workbook.sheet_names() {
+        let range = workbook
+            .worksheet_range(&name)
+            .map_err(|error| format!("Could not read sheet \"{name}\": {error}"))?;
+        let first_row = range.start().map_or(0, |(row, _)| row as usize);
+        let mut rows = SourceRows::default();
+        for (index, row) in range.rows().enumerate() {
+            let cells: Vec<String> = row.iter().map(cell_text).collect();
+            if cells.iter().all(String::is_empty) {
+                continue;
+            }
+            rows.rows.push(cells);
+            rows.line_numbers.push(first_row + index + 1);
+        }
+        sheets.push(Sheet { name, rows });
+    }
+    if sheets.is_empty() {
+        return Err("The workbook has no worksheets.".to_string());
+    }
+    Ok(sheets)
+}
+
+/// Worksheet names may not contain `[]:*?/\` and are limited to 31 characters.
+fn sheet_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '-' } else { c })
+        .take(31)
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('\'').to_string();
+    if cleaned.is_empty() {
+        "Flashcards".to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn column_width(field: ColumnField) -> f64 {
+    match field {
+        ColumnField::Word => 16.0,
+        ColumnField::Pinyin => 20.0,
+        ColumnField::Translation => 40.0,
+        ColumnField::Known => 10.0,
+        ColumnField::Tags => 24.0,
+        ColumnField::Ignore => 8.0,
+    }
+}
+
+/// Writes the chosen `columns` of the cards to a workbook, followed by a sheet
+/// summarising progress.
+pub fn export_flashcards_xlsx(
+    name: &str,
+    cards: &[Flashcard],
+    columns: &[ColumnField],
+) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let header = Format::new()
+        .set_bold()
+        .set_background_color(Color::RGB(HEADER_FILL))
+        .set_border_bottom(FormatBorder::Thin);
+    let known = Format::new().set_background_color(Color::RGB(KNOWN_FILL));
+
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(sheet_name(name))?;
+    for (column, field) in columns.iter().enumerate() {
+        let column = column as u16;
+        sheet.write_string_with_format(0, column, field_label(*field), &header)?;
+        sheet.set_column_width(column, column_width(*field))?;
+    }
+    for (index, card) in cards.iter().enumerate() {
+        let row = index as u32 + 1;
+        for (column, field) in columns.iter().enumerate() {
+            let column = column as u16;
+            match field {
+                ColumnField::Known if card.known => {
+                    sheet.write_boolean_with_format(row, column, true, &known)?;
+                }
+                ColumnField::Known => {
+                    sheet.write_boolean(row, column, false)?;
+                }
+                field => {
+                    sheet.write_string(row, column, field_text(card, *field))?;
+                }
+            }
+        }
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    if !columns.is_empty() {
+        sheet.autofilter(0, 0, cards.len() as u32, columns.len() as u16 - 1)?;
+    }
+
+    let known_count = cards.iter().filter(|card| card.known).count();
+    let percent = Format::new().set_num_format("0%");
+    let summary = workbook.add_worksheet();
+    summary.set_name("Progress")?;
+    summary.set_column_width(0, 16)?;
+    summary.write_string_with_format(0, 0, "Progress", &header)?;
+    summary.write_string_with_format(0, 1, "Cards", &header)?;
+    for (row, (label, count)) in [
+        ("Total", cards.len()),
+        ("Known", known_count),
+        ("Still learning", cards.len() - known_count),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        summary.write_string(row as u32 + 1, 0, label)?;
+        summary.write_number(row as u32 + 1, 1, count as f64)?;
+    }
+    summary.write_string(4, 0, "Known share")?;
+    let share = if cards.is_empty() {
+        0.0
+    } else {
+        known_count as f64 / cards.len() as f64
+    };
+    summary.write_number_with_format(4, 1, share, &percent)?;
+
+    workbook.save_to_buffer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_flashcards_xlsx, read_xlsx, sheet_name};
+    use crate::export::EXPORT_FIELDS;
+    use crate::import::{build_report, guess_mapping, SourceRows};
+    use crate::model::Flashcard;
+
+    fn card(word: &str, pinyin: &str, translation: &str, known: bool) -> Flashcard {
+        Flashcard {
+            word: word.to_string(),
+            pinyin: Some(pinyin.to_string()),
+            translation: translation.to_string(),
+            known,
+            tags: vec!["HSK1".to_string()],
+        }
+    }
+
+    #[test]
+    fn exported_workbooks_import_again() {
+        let cards = vec![
+            card("你好", "nǐ hǎo", "hello", false),
+            card("谢谢", "xièxie", "thank you", true),
+        ];
+        let bytes = export_flashcards_xlsx("HSK 1", &cards, &EXPORT_FIELDS).unwrap();
+        let sheets = read_xlsx(&bytes).unwrap();
+
+        assert_eq!(sheets.len(), 2);
+        assert_eq!(sheets[0].name, "HSK 1");
+        assert_eq!(sheets[1].name, "Progress");
+        let rows = &sheets[0].rows;
+        assert_eq!(rows.line_numbers, [1, 2, 3]);
+        let imported = build_report(rows, &guess_mapping(&rows.rows)).cards();
+        assert!(imported == cards);
+    }
+
+    #[test]
+    fn reads_values_of_every_cell_type_as_text() {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.write_string(2, 1, "一").unwrap();
+        sheet.write_number(2, 2, 1.0).unwrap();
+        sheet.write_number(4, 2, 2.5).unwrap();
+        let bytes = workbook.save_to_buffer().unwrap();
+
+        let rows: SourceRows = read_xlsx(&bytes).unwrap().remove(0).rows;
+        assert_eq!(rows.rows, [vec!["一", "1"], vec!["", "2.5"]]);
+        assert_eq!(rows.line_numbers, [3, 5]);
+        assert!(read_xlsx(b"word,translation").is_err());
+    }
+
+    #[test]
+    fn sheet_names_follow_excel_rules() {
+        assert_eq!(sheet_name("HSK / Unit 1: food?"), "HSK - Unit 1- food-");
+        assert_eq!(sheet_name(""), "Flashcards");
+        assert_eq!(sheet_name(&"长".repeat(40)).chars().count(), 31);
+    }
+}